@@ -0,0 +1,17 @@
+extern crate open_notify_api;
+
+use open_notify_api::mock::MockServer;
+use std::thread;
+use std::time::Duration;
+
+fn main() {
+    match MockServer::start() {
+        Ok(server) => {
+            println!("Serving open-notify fixtures on {}", server.url());
+            loop {
+                thread::sleep(Duration::from_secs(60));
+            }
+        }
+        Err(e) => eprintln!("{:?}", e),
+    }
+}
@@ -0,0 +1,349 @@
+//! Non-blocking client for the open-notify endpoints.
+//!
+//! Only available with the `async` feature. Responses are validated
+//! by the same parsers as the blocking `OpenNotifyClient` uses.
+//!
+//! A circuit breaker configured on the builder guards the async
+//! client as well. Retries are not supported: waiting between attempts
+//! would need a timer of the async runtime, so failed requests resolve
+//! to the error of the first attempt.
+
+use std::future::{self, Future};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use client::{endpoint_url, iss_pass_times_url, response_body, OpenNotifyClientBuilder};
+use error::OpenNotificationError;
+use request::IssPassRequest;
+use retry::CircuitBreaker;
+#[cfg(feature = "reqwest")]
+use transport::AsyncReqwestTransport;
+use transport::{AsyncTransport, TransportFuture};
+use {
+    astro_from_json, iss_now_from_json, iss_pass_times_from_json, iss_pass_times_from_json_checked,
+};
+use {Astros, IssNow, IssPassTimes};
+
+/// Async client to access the open-notify endpoints.
+///
+/// # Example
+#[cfg_attr(feature = "reqwest", doc = "```no_run")]
+#[cfg_attr(not(feature = "reqwest"), doc = "```ignore")]
+/// # extern crate futures;
+/// # extern crate open_notify_api;
+/// # fn main() {
+/// use open_notify_api::AsyncOpenNotifyClient;
+///
+/// let client = AsyncOpenNotifyClient::new().unwrap();
+/// let astros = futures::executor::block_on(client.astros());
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AsyncOpenNotifyClient<T> {
+    base_url: String,
+    verify_pass_times: bool,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    transport: T,
+}
+
+#[cfg(feature = "reqwest")]
+impl AsyncOpenNotifyClient<AsyncReqwestTransport> {
+    /// Creates an async client for the public open-notify api
+    /// with default settings.
+    pub fn new() -> Result<AsyncOpenNotifyClient<AsyncReqwestTransport>, OpenNotificationError> {
+        OpenNotifyClientBuilder::new().build_async()
+    }
+
+    /// Returns a builder to configure a client, finished with
+    /// `build_async`.
+    pub fn builder() -> OpenNotifyClientBuilder {
+        OpenNotifyClientBuilder::new()
+    }
+}
+
+impl<T: AsyncTransport> AsyncOpenNotifyClient<T> {
+    pub(crate) fn from_parts(
+        base_url: String,
+        verify_pass_times: bool,
+        circuit_breaker: Option<Arc<CircuitBreaker>>,
+        transport: T,
+    ) -> AsyncOpenNotifyClient<T> {
+        AsyncOpenNotifyClient {
+            base_url,
+            verify_pass_times,
+            circuit_breaker,
+            transport,
+        }
+    }
+
+    /// Creates an async client for the public open-notify api
+    /// using the given transport.
+    pub fn with_transport(transport: T) -> AsyncOpenNotifyClient<T> {
+        OpenNotifyClientBuilder::new().build_async_with_transport(transport)
+    }
+
+    /// Base url all endpoints are resolved against.
+    pub fn base_url(&self) -> &str {
+        self.base_url.as_str()
+    }
+
+    /// Transport used to issue the requests.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Circuit breaker guarding the requests, if any.
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.circuit_breaker.as_deref()
+    }
+
+    /// Fetch astronouts currently in space.
+    pub fn astros(&self) -> Fetch<'_, Astros> {
+        self.fetch(
+            &endpoint_url(&self.base_url, "astros.json"),
+            astro_from_json,
+        )
+    }
+
+    /// Fetch current ISS position.
+    pub fn iss_now(&self) -> Fetch<'_, IssNow> {
+        self.fetch(
+            &endpoint_url(&self.base_url, "iss-now.json"),
+            iss_now_from_json,
+        )
+    }
+
+    /// Request ISS pass times over a specified location.
+    ///
+    /// See [`iss_pass_times`](../fn.iss_pass_times.html) for
+    /// the valid parameter ranges.
+    pub fn iss_pass_times(&self, lat: f32, lon: f32, alt: f32, n: u32) -> Fetch<'_, IssPassTimes> {
+        let request = IssPassRequest::builder(lat, lon)
+            .altitude(alt)
+            .passes(n)
+            .build();
+        match request {
+            Ok(request) => self.iss_pass_times_for(&request),
+            Err(e) => Fetch::new(Box::pin(future::ready(Err(e))), iss_pass_times_from_json),
+        }
+    }
+
+    /// Request ISS pass times as described by `request`.
+    ///
+    /// The response is checked against `request` if enabled with
+    /// `OpenNotifyClientBuilder::verify_pass_times`.
+    pub fn iss_pass_times_for(&self, request: &IssPassRequest) -> Fetch<'_, IssPassTimes> {
+        let url = iss_pass_times_url(&self.base_url, request);
+        if self.verify_pass_times {
+            let request = request.clone();
+            self.fetch(&url, move |data: &str| {
+                iss_pass_times_from_json_checked(data, &request)
+            })
+        } else {
+            self.fetch(&url, iss_pass_times_from_json)
+        }
+    }
+
+    fn fetch<'a, R, F>(&'a self, url: &str, parse: F) -> Fetch<'a, R>
+    where
+        F: FnOnce(&str) -> Result<R, OpenNotificationError> + Send + 'a,
+    {
+        let mut fetch = Fetch::new(self.transport.get(url), parse);
+        fetch.circuit_breaker = self.circuit_breaker.as_deref();
+        fetch
+    }
+}
+
+/// Future returned by the `AsyncOpenNotifyClient` methods.
+///
+/// Resolves to the parsed and validated response. A circuit breaker
+/// is asked on the first poll, so the request is only counted once the
+/// future runs. Dropping it before the response arrived gives up its
+/// turn as the trial request of a half open breaker.
+pub struct Fetch<'a, R> {
+    response: TransportFuture<'a>,
+    parse: Option<Parser<'a, R>>,
+    circuit_breaker: Option<&'a CircuitBreaker>,
+    admitted: bool,
+}
+
+type Parser<'a, R> = Box<dyn FnOnce(&str) -> Result<R, OpenNotificationError> + Send + 'a>;
+
+impl<'a, R> Fetch<'a, R> {
+    fn new<F>(response: TransportFuture<'a>, parse: F) -> Fetch<'a, R>
+    where
+        F: FnOnce(&str) -> Result<R, OpenNotificationError> + Send + 'a,
+    {
+        Fetch {
+            response,
+            parse: Some(Box::new(parse)),
+            circuit_breaker: None,
+            admitted: false,
+        }
+    }
+}
+
+impl<'a, R> Future for Fetch<'a, R> {
+    type Output = Result<R, OpenNotificationError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if let Some(breaker) = self.circuit_breaker {
+            if !self.admitted {
+                if !breaker.allow() {
+                    self.circuit_breaker = None;
+                    self.parse = None;
+                    return Poll::Ready(Err(OpenNotificationError::CircuitOpen));
+                }
+                self.admitted = true;
+            }
+        }
+
+        let body = match self.response.as_mut().poll(cx) {
+            Poll::Ready(response) => response.and_then(|response| response_body(&response)),
+            Poll::Pending => return Poll::Pending,
+        };
+        if let Some(breaker) = self.circuit_breaker.take() {
+            match body {
+                Err(ref e) if e.is_transient() => breaker.record_failure(),
+                _ => breaker.record_success(),
+            }
+        }
+        let parse = self.parse.take().expect("Fetch polled after completion");
+        Poll::Ready(body.and_then(|body| parse(&body)))
+    }
+}
+
+impl<'a, R> Drop for Fetch<'a, R> {
+    fn drop(&mut self) {
+        if let Some(breaker) = self.circuit_breaker {
+            if self.admitted {
+                breaker.record_abandoned();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::task::noop_waker;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use std::time::Duration;
+    use transport::{MemoryTransport, Response};
+
+    /// Never answers the first request sent, answers later ones with
+    /// a position.
+    struct Stalling {
+        stalled: AtomicBool,
+    }
+
+    impl AsyncTransport for Stalling {
+        fn get(&self, _url: &str) -> TransportFuture<'_> {
+            let mut stall = None;
+            Box::pin(future::poll_fn(move |_| {
+                if *stall.get_or_insert_with(|| !self.stalled.swap(true, Ordering::SeqCst)) {
+                    return Poll::Pending;
+                }
+                Poll::Ready(Ok(Response::new(
+                    200,
+                    r#"{"message": "success", "timestamp": 1521971230,
+                    "iss_position": {"latitude": -34.6445, "longitude": 73.5964}}"#,
+                )))
+            }))
+        }
+    }
+
+    #[test]
+    fn async_client_iss_now() {
+        let mut transport = MemoryTransport::new();
+        transport.insert(
+            "http://localhost/iss-now.json",
+            200,
+            r#"{
+            "iss_position": {"longitude": 73.5964, "latitude": -34.6445},
+            "message": "success",
+            "timestamp": 1521971230}"#,
+        );
+
+        let client = OpenNotifyClientBuilder::new()
+            .base_url("http://localhost")
+            .build_async_with_transport(transport);
+
+        let iss_now = block_on(client.iss_now()).unwrap();
+        assert_eq!(iss_now.timestamp(), 1521971230);
+        assert_eq!(iss_now.latitude(), -34.6445);
+        assert_eq!(iss_now.longitude(), 73.5964);
+    }
+
+    #[test]
+    fn async_client_astros_inconsistent_data() {
+        let mut transport = MemoryTransport::new();
+        transport.insert(
+            "http://api.open-notify.org/astros.json",
+            200,
+            r#"{
+            "message": "success",
+            "number": 2,
+            "people": [{"name": "Anton Shkaplerov", "craft": "ISS"}]
+            }"#,
+        );
+
+        let client = AsyncOpenNotifyClient::with_transport(transport);
+
+        match block_on(client.astros()) {
+            Err(OpenNotificationError::Data(_)) => {}
+            _ => panic!("expected a data error"),
+        }
+    }
+
+    #[test]
+    fn async_client_circuit_breaker() {
+        let mut transport = MemoryTransport::new();
+        transport.insert("http://api.open-notify.org/iss-now.json", 503, "down");
+
+        let client = OpenNotifyClientBuilder::new()
+            .circuit_breaker(CircuitBreaker::new(2, Duration::from_secs(60)))
+            .build_async_with_transport(transport);
+
+        for _ in 0..2 {
+            match block_on(client.iss_now()) {
+                Err(OpenNotificationError::Http { status: 503, .. }) => {}
+                _ => panic!("expected an http error"),
+            }
+        }
+        assert!(client.circuit_breaker().unwrap().is_open());
+        match block_on(client.iss_now()) {
+            Err(OpenNotificationError::CircuitOpen) => {}
+            _ => panic!("expected an open circuit"),
+        }
+        assert_eq!(client.transport().requests().len(), 2);
+    }
+
+    #[test]
+    fn async_client_circuit_breaker_abandoned_trial() {
+        let client = OpenNotifyClientBuilder::new()
+            .circuit_breaker(CircuitBreaker::new(1, Duration::from_millis(10)))
+            .build_async_with_transport(Stalling {
+                stalled: AtomicBool::new(false),
+            });
+        let breaker = client.circuit_breaker().unwrap();
+        breaker.record_failure();
+        thread::sleep(Duration::from_millis(20));
+
+        // Creating a future doesn't use up the trial.
+        drop(client.iss_now());
+
+        let mut trial = client.iss_now();
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert!(Pin::new(&mut trial).poll(&mut cx).is_pending());
+        assert!(breaker.is_open());
+        drop(trial);
+
+        assert_eq!(block_on(client.iss_now()).unwrap().timestamp(), 1521971230);
+        assert!(!breaker.is_open());
+    }
+}
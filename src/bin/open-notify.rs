@@ -0,0 +1,424 @@
+//! Command line tool to query open-notify.
+//!
+//! ```text
+//! open-notify [--format table|json|csv] [--base-url URL] [--timeout SECONDS] <command>
+//!
+//! Commands:
+//!   astros                                  People in space right now
+//!   now                                     Current position of the ISS
+//!   passes --lat LAT --lon LON [--alt ALT] [-n N]
+//!                                           Upcoming ISS passes over a location
+//! ```
+
+extern crate chrono;
+extern crate open_notify_api;
+#[macro_use]
+extern crate serde_json;
+
+use std::env;
+use std::process;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use open_notify_api::error::OpenNotificationError;
+use open_notify_api::transport::ReqwestTransport;
+use open_notify_api::{Astros, IssNow, IssPassTimes, OpenNotifyClient};
+
+const USAGE: &str = "Usage: open-notify [OPTIONS] <COMMAND>
+
+Commands:
+  astros                      People in space right now
+  now                         Current position of the ISS
+  passes --lat LAT --lon LON [--alt ALT] [-n N]
+                              Upcoming ISS passes over a location
+
+Options:
+  -f, --format FORMAT         Output format: table (default), json or csv
+      --base-url URL          Server providing the open-notify api
+      --timeout SECONDS       Request timeout
+  -h, --help                  Print this help";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Astros,
+    Now,
+    Passes {
+        lat: f32,
+        lon: f32,
+        alt: f32,
+        n: u32,
+    },
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    format: Format,
+    base_url: Option<String>,
+    timeout: Option<Duration>,
+    command: Command,
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("error: {}\n\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    };
+
+    if options.command == Command::Help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    match run(&options) {
+        Ok(output) => print!("{}", output),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn run(options: &Options) -> Result<String, OpenNotificationError> {
+    let client = client(options)?;
+
+    Ok(match options.command {
+        Command::Astros => format_astros(&client.astros()?, options.format),
+        Command::Now => format_iss_now(&client.iss_now()?, options.format),
+        Command::Passes { lat, lon, alt, n } => {
+            format_passes(&client.iss_pass_times(lat, lon, alt, n)?, options.format)
+        }
+        Command::Help => String::from(USAGE),
+    })
+}
+
+fn client(options: &Options) -> Result<OpenNotifyClient<ReqwestTransport>, OpenNotificationError> {
+    let mut builder =
+        OpenNotifyClient::builder().user_agent(concat!("open-notify/", env!("CARGO_PKG_VERSION")));
+    if let Some(ref base_url) = options.base_url {
+        builder = builder.base_url(base_url);
+    }
+    if let Some(timeout) = options.timeout {
+        builder = builder.timeout(timeout);
+    }
+    builder.build()
+}
+
+fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<Options, String> {
+    let args: Vec<String> = args.collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        return Ok(Options {
+            format: Format::Table,
+            base_url: None,
+            timeout: None,
+            command: Command::Help,
+        });
+    }
+
+    let mut format = Format::Table;
+    let mut base_url = None;
+    let mut timeout = None;
+    let mut command = None;
+    let (mut lat, mut lon, mut alt, mut n) = (None, None, 100.0, 5);
+    let mut passes_option = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if ["--lat", "--lon", "--alt", "-n"].contains(&arg.as_str()) && passes_option.is_none() {
+            passes_option = Some(arg.clone());
+        }
+        match arg.as_str() {
+            "-f" | "--format" => {
+                format = match value(&mut args, &arg)?.as_str() {
+                    "table" => Format::Table,
+                    "json" => Format::Json,
+                    "csv" => Format::Csv,
+                    other => return Err(format!("unknown format '{}'", other)),
+                }
+            }
+            "--base-url" => base_url = Some(value(&mut args, &arg)?),
+            "--timeout" => timeout = Some(Duration::from_secs(number(&mut args, &arg)?)),
+            "--lat" => lat = Some(number(&mut args, &arg)?),
+            "--lon" => lon = Some(number(&mut args, &arg)?),
+            "--alt" => alt = number(&mut args, &arg)?,
+            "-n" => n = number(&mut args, &arg)?,
+            "astros" | "now" | "passes" if command.is_none() => {
+                command = Some(match arg.as_str() {
+                    "astros" => Command::Astros,
+                    "now" => Command::Now,
+                    _ => Command::Passes {
+                        lat: 0.0,
+                        lon: 0.0,
+                        alt: 0.0,
+                        n: 0,
+                    },
+                })
+            }
+            other => return Err(format!("unexpected argument '{}'", other)),
+        }
+    }
+
+    let command = match command {
+        None => return Err(String::from("no command given")),
+        Some(Command::Passes { .. }) => Command::Passes {
+            lat: lat.ok_or("passes requires --lat")?,
+            lon: lon.ok_or("passes requires --lon")?,
+            alt,
+            n,
+        },
+        Some(command) => {
+            if let Some(option) = passes_option {
+                return Err(format!("{} is only valid for passes", option));
+            }
+            command
+        }
+    };
+
+    Ok(Options {
+        format,
+        base_url,
+        timeout,
+        command,
+    })
+}
+
+fn value<I: Iterator<Item = String>>(args: &mut I, name: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("{} requires a value", name))
+}
+
+fn number<T: FromStr, I: Iterator<Item = String>>(args: &mut I, name: &str) -> Result<T, String> {
+    let value = value(args, name)?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for {}", value, name))
+}
+
+fn format_astros(astros: &Astros, format: Format) -> String {
+    let people = astros.people();
+    match format {
+        Format::Json => json_line(&json!({
+            "number": people.len(),
+            "people": people,
+        })),
+        Format::Csv => csv(
+            &["name", "craft"],
+            people
+                .iter()
+                .map(|p| vec![p.name().to_string(), p.craft().to_string()])
+                .collect(),
+        ),
+        Format::Table => {
+            let mut out = table(
+                &["NAME", "CRAFT"],
+                people
+                    .iter()
+                    .map(|p| vec![p.name().to_string(), p.craft().to_string()])
+                    .collect(),
+            );
+            out.push_str(&format!("\n{} people in space\n", people.len()));
+            out
+        }
+    }
+}
+
+fn format_iss_now(iss_now: &IssNow, format: Format) -> String {
+    match format {
+        Format::Json => json_line(&json!({
+            "timestamp": iss_now.timestamp(),
+            "latitude": iss_now.latitude_f64(),
+            "longitude": iss_now.longitude_f64(),
+        })),
+        Format::Csv => csv(
+            &["timestamp", "latitude", "longitude"],
+            vec![vec![
+                iss_now.timestamp().to_string(),
+                iss_now.latitude().to_string(),
+                iss_now.longitude().to_string(),
+            ]],
+        ),
+        Format::Table => table(
+            &["TIME", "LATITUDE", "LONGITUDE"],
+            vec![vec![
+                human_time(iss_now.timestamp()),
+                iss_now.latitude().to_string(),
+                iss_now.longitude().to_string(),
+            ]],
+        ),
+    }
+}
+
+fn format_passes(pass_times: &IssPassTimes, format: Format) -> String {
+    let passes = pass_times.passes();
+    match format {
+        Format::Json => json_line(&json!(passes
+            .iter()
+            .map(|p| json!({
+                "risetime": p.rise(),
+                "settime": p.set_time(),
+                "duration": p.duration(),
+            }))
+            .collect::<Vec<_>>())),
+        Format::Csv => csv(
+            &["risetime", "settime", "duration"],
+            passes
+                .iter()
+                .map(|p| {
+                    vec![
+                        p.rise().to_string(),
+                        p.set_time().to_string(),
+                        p.duration().to_string(),
+                    ]
+                })
+                .collect(),
+        ),
+        Format::Table => table(
+            &["RISE", "SET", "DURATION"],
+            passes
+                .iter()
+                .map(|p| {
+                    vec![
+                        human_time(p.rise()),
+                        human_time(p.set_time()),
+                        human_duration(p.duration()),
+                    ]
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn human_time(timestamp: i64) -> String {
+    match DateTime::<Utc>::from_timestamp(timestamp, 0) {
+        Some(time) => time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+fn human_duration(seconds: i64) -> String {
+    if seconds < 60 {
+        format!("{}s", seconds)
+    } else {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+fn json_line(value: &serde_json::Value) -> String {
+    format!("{}\n", value)
+}
+
+fn csv(header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut out = header.join(",");
+    out.push('\n');
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+fn table(header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+    for row in rows.iter() {
+        for (width, field) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(field.chars().count());
+        }
+    }
+
+    let line = |fields: Vec<&str>| -> String {
+        let padded: Vec<String> = fields
+            .iter()
+            .zip(widths.iter())
+            .map(|(f, w)| format!("{:w$}", f, w = w))
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+
+    let mut out = line(header.to_vec());
+    for row in rows.iter() {
+        out.push_str(&line(row.iter().map(|f| f.as_str()).collect()));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Result<Options, String> {
+        parse_args(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn parse_passes() {
+        let options = args("--format csv passes --lat 52.5 --lon 13.4 -n 3").unwrap();
+        assert_eq!(options.format, Format::Csv);
+        assert_eq!(
+            options.command,
+            Command::Passes {
+                lat: 52.5,
+                lon: 13.4,
+                alt: 100.0,
+                n: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(args("").is_err());
+        assert!(args("passes --lat 52.5").is_err());
+        assert!(args("now --format xml").is_err());
+        assert!(args("now --timeout soon").is_err());
+        assert!(args("now astros").is_err());
+        assert!(args("now --lat 52.5").is_err());
+        assert!(args("-n 3 astros").is_err());
+    }
+
+    #[test]
+    fn parse_help_anywhere() {
+        for line in &[
+            "-h",
+            "now --help",
+            "passes --lat 52.5 -h",
+            "--format xml -h now",
+        ] {
+            assert_eq!(args(line).unwrap().command, Command::Help);
+        }
+    }
+
+    #[test]
+    fn csv_quoting() {
+        assert_eq!(
+            csv(&["name"], vec![vec![String::from("Doe, \"J\"")]]),
+            "name\n\"Doe, \"\"J\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn human_readable() {
+        assert_eq!(human_time(1521971230), "2018-03-25 09:47:10 UTC");
+        assert_eq!(human_duration(42), "42s");
+        assert_eq!(human_duration(496), "8m 16s");
+    }
+}
@@ -0,0 +1,476 @@
+//! Response caching for `OpenNotifyClient`.
+//!
+//! `CachedClient` wraps a client and keeps successful responses for a
+//! configurable time per endpoint. Pass times are cached per request
+//! url, i.e. per latitude, longitude, altitude and number of passes.
+//! Failed requests are never cached.
+//!
+//! Entries live in a `CacheBackend`. `MemoryCache` is used by default,
+//! `DiskCache` keeps them in a directory so they survive restarts.
+//! Independent of the TTLs no entry older than the configured max-age
+//! is ever served.
+//!
+//! # Example
+//! ```
+//! use std::time::Duration;
+//! use open_notify_api::cache::CachedClientBuilder;
+//! use open_notify_api::transport::MemoryTransport;
+//! use open_notify_api::OpenNotifyClient;
+//!
+//! let mut transport = MemoryTransport::new();
+//! transport.insert(
+//!     "http://api.open-notify.org/astros.json",
+//!     200,
+//!     r#"{"message": "success", "number": 2, "people": [
+//!         {"name": "Anton Shkaplerov", "craft": "ISS"},
+//!         {"name": "Scott Tingle", "craft": "ISS"}]}"#,
+//! );
+//!
+//! let client = CachedClientBuilder::new()
+//!     .iss_now_ttl(Duration::from_secs(2))
+//!     .build(OpenNotifyClient::with_transport(transport));
+//!
+//! // Only the first call reaches open-notify.
+//! for _ in 0..10 {
+//!     println!("{}", client.astros().unwrap().people().len());
+//! }
+//! assert_eq!(client.client().transport().requests().len(), 1);
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json;
+
+use client::{endpoint_url, iss_pass_times_url, OpenNotifyClient};
+use error::OpenNotificationError;
+use request::IssPassRequest;
+use transport::Transport;
+use {Astros, IssNow, IssPassTimes};
+
+/// Default time to live of `astros.json` responses.
+pub const DEFAULT_ASTROS_TTL: Duration = Duration::from_secs(3600);
+/// Default time to live of `iss-now.json` responses.
+pub const DEFAULT_ISS_NOW_TTL: Duration = Duration::from_secs(5);
+/// Default time to live of `iss-pass.json` responses.
+pub const DEFAULT_ISS_PASS_TIMES_TTL: Duration = Duration::from_secs(600);
+/// Default age after which entries are never served.
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(86400);
+
+/// Cached value together with the time it was stored.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CacheEntry {
+    key: String,
+    stored: u64,
+    value: String,
+}
+
+impl CacheEntry {
+    /// Creates an entry for `key` stored at unix time `stored`
+    /// in milliseconds.
+    pub fn new(key: &str, stored: u64, value: &str) -> CacheEntry {
+        CacheEntry {
+            key: String::from(key),
+            stored,
+            value: String::from(value),
+        }
+    }
+
+    /// Key the entry is stored under.
+    pub fn key(&self) -> &str {
+        self.key.as_str()
+    }
+
+    /// Unix time in milliseconds the entry was stored at.
+    pub fn stored(&self) -> u64 {
+        self.stored
+    }
+
+    /// Cached value.
+    pub fn value(&self) -> &str {
+        self.value.as_str()
+    }
+
+    fn is_fresh(&self, now: u64, ttl: Duration) -> bool {
+        self.stored <= now && u128::from(now - self.stored) < ttl.as_millis()
+    }
+}
+
+/// Storage for cache entries.
+///
+/// Backends are shared by reference, so implementations use interior
+/// mutability. Storage failures must not fail a request and are
+/// reported as a miss instead.
+pub trait CacheBackend {
+    /// Entry stored under `key`, regardless of its age.
+    fn get(&self, key: &str) -> Option<CacheEntry>;
+
+    /// Stores `entry`, replacing a previous entry with the same key.
+    fn put(&self, entry: CacheEntry);
+}
+
+impl<B: CacheBackend + ?Sized> CacheBackend for &B {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        (**self).get(key)
+    }
+
+    fn put(&self, entry: CacheEntry) {
+        (**self).put(entry)
+    }
+}
+
+/// Keeps cache entries in memory.
+#[derive(Default)]
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl MemoryCache {
+    pub fn new() -> MemoryCache {
+        MemoryCache::default()
+    }
+}
+
+impl CacheBackend for MemoryCache {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    fn put(&self, entry: CacheEntry) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(entry.key.clone(), entry);
+    }
+}
+
+/// Keeps cache entries as JSON files in a directory.
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// Uses `dir` for the cache entries, creating it if needed.
+    pub fn new<P: AsRef<Path>>(dir: P) -> io::Result<DiskCache> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(DiskCache {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Directory holding the cache entries.
+    pub fn dir(&self) -> &Path {
+        self.dir.as_path()
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let name: String = key
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.json", name))
+    }
+}
+
+impl CacheBackend for DiskCache {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        let data = fs::read_to_string(self.path(key)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&data).ok()?;
+        // File names are not unique for all keys.
+        if entry.key == key {
+            Some(entry)
+        } else {
+            None
+        }
+    }
+
+    fn put(&self, entry: CacheEntry) {
+        let path = self.path(&entry.key);
+        let tmp = path.with_extension("tmp");
+        if let Ok(data) = serde_json::to_string(&entry) {
+            if fs::write(&tmp, data).is_ok() {
+                let _ = fs::rename(&tmp, &path);
+            }
+        }
+    }
+}
+
+/// `OpenNotifyClient` serving repeated requests from a cache.
+pub struct CachedClient<T, B = MemoryCache> {
+    client: OpenNotifyClient<T>,
+    backend: B,
+    astros_ttl: Duration,
+    iss_now_ttl: Duration,
+    iss_pass_times_ttl: Duration,
+    max_age: Duration,
+}
+
+impl<T: Transport> CachedClient<T> {
+    /// Caches responses of `client` in memory with the default TTLs.
+    pub fn new(client: OpenNotifyClient<T>) -> CachedClient<T> {
+        CachedClientBuilder::new().build(client)
+    }
+}
+
+impl<T: Transport, B: CacheBackend> CachedClient<T, B> {
+    /// Wrapped client.
+    pub fn client(&self) -> &OpenNotifyClient<T> {
+        &self.client
+    }
+
+    /// Backend holding the cache entries.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Astronauts currently in space.
+    pub fn astros(&self) -> Result<Astros, OpenNotificationError> {
+        let key = endpoint_url(self.client.base_url(), "astros.json");
+        self.cached(&key, self.astros_ttl, || self.client.astros())
+    }
+
+    /// Current ISS position.
+    pub fn iss_now(&self) -> Result<IssNow, OpenNotificationError> {
+        let key = endpoint_url(self.client.base_url(), "iss-now.json");
+        self.cached(&key, self.iss_now_ttl, || self.client.iss_now())
+    }
+
+    /// ISS pass times over a specified location.
+    pub fn iss_pass_times(
+        &self,
+        lat: f32,
+        lon: f32,
+        alt: f32,
+        n: u32,
+    ) -> Result<IssPassTimes, OpenNotificationError> {
+        let request = IssPassRequest::builder(lat, lon)
+            .altitude(alt)
+            .passes(n)
+            .build()?;
+        self.iss_pass_times_for(&request)
+    }
+
+    /// ISS pass times as described by `request`.
+    pub fn iss_pass_times_for(
+        &self,
+        request: &IssPassRequest,
+    ) -> Result<IssPassTimes, OpenNotificationError> {
+        let key = iss_pass_times_url(self.client.base_url(), request);
+        self.cached(&key, self.iss_pass_times_ttl, || {
+            self.client.iss_pass_times_for(request)
+        })
+    }
+
+    fn cached<R, F>(&self, key: &str, ttl: Duration, fetch: F) -> Result<R, OpenNotificationError>
+    where
+        R: Serialize + DeserializeOwned,
+        F: FnOnce() -> Result<R, OpenNotificationError>,
+    {
+        let now = now_millis();
+        let ttl = ttl.min(self.max_age);
+
+        if let Some(entry) = self.backend.get(key) {
+            if entry.is_fresh(now, ttl) {
+                if let Ok(value) = serde_json::from_str(&entry.value) {
+                    return Ok(value);
+                }
+            }
+        }
+
+        let value = fetch()?;
+        if let Ok(data) = serde_json::to_string(&value) {
+            self.backend.put(CacheEntry::new(key, now, &data));
+        }
+        Ok(value)
+    }
+}
+
+/// Builder for `CachedClient`.
+pub struct CachedClientBuilder {
+    astros_ttl: Duration,
+    iss_now_ttl: Duration,
+    iss_pass_times_ttl: Duration,
+    max_age: Duration,
+}
+
+impl CachedClientBuilder {
+    pub fn new() -> CachedClientBuilder {
+        CachedClientBuilder {
+            astros_ttl: DEFAULT_ASTROS_TTL,
+            iss_now_ttl: DEFAULT_ISS_NOW_TTL,
+            iss_pass_times_ttl: DEFAULT_ISS_PASS_TIMES_TTL,
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+
+    /// How long `astros` responses are served from the cache.
+    pub fn astros_ttl(mut self, ttl: Duration) -> CachedClientBuilder {
+        self.astros_ttl = ttl;
+        self
+    }
+
+    /// How long `iss_now` responses are served from the cache.
+    pub fn iss_now_ttl(mut self, ttl: Duration) -> CachedClientBuilder {
+        self.iss_now_ttl = ttl;
+        self
+    }
+
+    /// How long pass times are served from the cache.
+    pub fn iss_pass_times_ttl(mut self, ttl: Duration) -> CachedClientBuilder {
+        self.iss_pass_times_ttl = ttl;
+        self
+    }
+
+    /// Age after which entries are never served, whatever the TTL.
+    pub fn max_age(mut self, max_age: Duration) -> CachedClientBuilder {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Wraps `client` with an in-memory cache.
+    pub fn build<T: Transport>(self, client: OpenNotifyClient<T>) -> CachedClient<T> {
+        self.build_with_backend(client, MemoryCache::new())
+    }
+
+    /// Wraps `client` with a cache kept in `backend`.
+    pub fn build_with_backend<T: Transport, B: CacheBackend>(
+        self,
+        client: OpenNotifyClient<T>,
+        backend: B,
+    ) -> CachedClient<T, B> {
+        CachedClient {
+            client,
+            backend,
+            astros_ttl: self.astros_ttl,
+            iss_now_ttl: self.iss_now_ttl,
+            iss_pass_times_ttl: self.iss_pass_times_ttl,
+            max_age: self.max_age,
+        }
+    }
+}
+
+impl Default for CachedClientBuilder {
+    fn default() -> CachedClientBuilder {
+        CachedClientBuilder::new()
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::OpenNotifyClientBuilder;
+    use mock::fixtures;
+    use tests::temp_path;
+    use transport::MemoryTransport;
+
+    fn transport() -> MemoryTransport {
+        let mut transport = MemoryTransport::new();
+        transport.insert("http://localhost/astros.json", 200, fixtures::ASTROS);
+        transport.insert("http://localhost/iss-now.json", 200, fixtures::ISS_NOW);
+        transport.insert(
+            "http://localhost/iss-pass.json?lat=52.5&lon=13.4&alt=10&n=5",
+            200,
+            fixtures::ISS_PASS,
+        );
+        transport
+    }
+
+    fn client(transport: &MemoryTransport) -> OpenNotifyClient<&MemoryTransport> {
+        OpenNotifyClientBuilder::new()
+            .base_url("http://localhost")
+            .build_with_transport(transport)
+    }
+
+    #[test]
+    fn serves_from_cache() {
+        let transport = transport();
+        let cached = CachedClient::new(client(&transport));
+
+        assert_eq!(cached.astros().unwrap().people().len(), 6);
+        assert_eq!(cached.astros().unwrap().people().len(), 6);
+        assert_eq!(cached.iss_now().unwrap().timestamp(), 1521971230);
+        assert_eq!(cached.iss_now().unwrap().timestamp(), 1521971230);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn pass_times_keyed_by_request() {
+        let transport = transport();
+        let cached = CachedClient::new(client(&transport));
+
+        assert!(cached.iss_pass_times(52.5, 13.4, 10.0, 5).is_ok());
+        assert!(cached.iss_pass_times(52.5, 13.4, 10.0, 5).is_ok());
+        assert!(cached.iss_pass_times(52.5, 13.4, 10.0, 4).is_err());
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn max_age_caps_ttl() {
+        let transport = transport();
+        let cached = CachedClientBuilder::new()
+            .astros_ttl(Duration::from_secs(3600))
+            .max_age(Duration::from_secs(0))
+            .build(client(&transport));
+
+        assert!(cached.astros().is_ok());
+        assert!(cached.astros().is_ok());
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn stale_entries_refetched() {
+        let transport = transport();
+        let backend = MemoryCache::new();
+        backend.put(CacheEntry::new(
+            "http://localhost/iss-now.json",
+            now_millis() - 60_000,
+            r#"{"message": "success", "timestamp": 1}"#,
+        ));
+        let cached = CachedClientBuilder::new().build_with_backend(client(&transport), &backend);
+
+        assert_eq!(cached.iss_now().unwrap().timestamp(), 1521971230);
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[test]
+    fn failures_not_cached() {
+        let transport = MemoryTransport::new();
+        let cached = CachedClient::new(client(&transport));
+
+        assert!(cached.astros().is_err());
+        assert!(cached.astros().is_err());
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn disk_cache_survives_restart() {
+        let dir = temp_path("cache");
+        let transport = transport();
+
+        {
+            let cached = CachedClientBuilder::new()
+                .build_with_backend(client(&transport), DiskCache::new(&dir).unwrap());
+            assert!(cached.astros().is_ok());
+        }
+
+        let cached = CachedClientBuilder::new()
+            .build_with_backend(client(&transport), DiskCache::new(&dir).unwrap());
+        assert_eq!(cached.astros().unwrap().people().len(), 6);
+        assert_eq!(transport.requests().len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,415 @@
+//! Configurable client for the open-notify endpoints.
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[cfg(feature = "async")]
+use async_client::AsyncOpenNotifyClient;
+use error::OpenNotificationError;
+use request::IssPassRequest;
+use retry::{CircuitBreaker, RetryPolicy};
+#[cfg(all(feature = "async", feature = "reqwest"))]
+use transport::AsyncReqwestTransport;
+#[cfg(feature = "async")]
+use transport::AsyncTransport;
+#[cfg(feature = "reqwest")]
+use transport::ReqwestTransport;
+use transport::{Response, Transport};
+use {
+    astro_from_json, iss_now_from_json, iss_pass_times_from_json, iss_pass_times_from_json_checked,
+};
+use {Astros, IssNow, IssPassTimes};
+
+/// Base url of the public open-notify api.
+pub const DEFAULT_BASE_URL: &str = "http://api.open-notify.org";
+
+/// Client to access the open-notify endpoints.
+///
+/// The client is generic over the `Transport` used to issue the
+/// requests. With the default `ReqwestTransport` the underlying
+/// connection pool is shared between clones, so a client should be
+/// created once and reused.
+///
+/// # Example
+#[cfg_attr(feature = "reqwest", doc = "```no_run")]
+#[cfg_attr(not(feature = "reqwest"), doc = "```ignore")]
+/// use std::time::Duration;
+/// use open_notify_api::OpenNotifyClient;
+///
+/// let client = OpenNotifyClient::builder()
+///     .base_url("http://localhost:8080")
+///     .timeout(Duration::from_secs(5))
+///     .user_agent("my-dashboard/1.0")
+///     .build()
+///     .unwrap();
+///
+/// if let Ok(iss_now) = client.iss_now() {
+///     println!("{} {}", iss_now.latitude(), iss_now.longitude());
+/// }
+/// ```
+#[derive(Clone)]
+pub struct OpenNotifyClient<T> {
+    base_url: String,
+    verify_pass_times: bool,
+    retry: Option<RetryPolicy>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    transport: T,
+}
+
+#[cfg(feature = "reqwest")]
+impl OpenNotifyClient<ReqwestTransport> {
+    /// Creates a client for the public open-notify api
+    /// with default settings.
+    pub fn new() -> Result<OpenNotifyClient<ReqwestTransport>, OpenNotificationError> {
+        OpenNotifyClient::builder().build()
+    }
+
+    /// Returns a builder to configure a client.
+    pub fn builder() -> OpenNotifyClientBuilder {
+        OpenNotifyClientBuilder::new()
+    }
+}
+
+impl<T: Transport> OpenNotifyClient<T> {
+    /// Creates a client for the public open-notify api
+    /// using the given transport.
+    pub fn with_transport(transport: T) -> OpenNotifyClient<T> {
+        OpenNotifyClientBuilder::new().build_with_transport(transport)
+    }
+
+    /// Transport used to issue the requests.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Base url all endpoints are resolved against.
+    pub fn base_url(&self) -> &str {
+        self.base_url.as_str()
+    }
+
+    /// Circuit breaker guarding the requests, if any.
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.circuit_breaker.as_deref()
+    }
+
+    /// Fetch astronouts currently in space.
+    pub fn astros(&self) -> Result<Astros, OpenNotificationError> {
+        astro_from_json(&self.get(&endpoint_url(&self.base_url, "astros.json"))?)
+    }
+
+    /// Fetch current ISS position.
+    pub fn iss_now(&self) -> Result<IssNow, OpenNotificationError> {
+        iss_now_from_json(&self.get(&endpoint_url(&self.base_url, "iss-now.json"))?)
+    }
+
+    /// Request ISS pass times over a specified location.
+    ///
+    /// See [`iss_pass_times`](../fn.iss_pass_times.html) for
+    /// the valid parameter ranges.
+    pub fn iss_pass_times(
+        &self,
+        lat: f32,
+        lon: f32,
+        alt: f32,
+        n: u32,
+    ) -> Result<IssPassTimes, OpenNotificationError> {
+        let request = IssPassRequest::builder(lat, lon)
+            .altitude(alt)
+            .passes(n)
+            .build()?;
+        self.iss_pass_times_for(&request)
+    }
+
+    /// Request ISS pass times as described by `request`.
+    ///
+    /// The response is checked against `request` if enabled with
+    /// `OpenNotifyClientBuilder::verify_pass_times`.
+    pub fn iss_pass_times_for(
+        &self,
+        request: &IssPassRequest,
+    ) -> Result<IssPassTimes, OpenNotificationError> {
+        let data = self.get(&iss_pass_times_url(&self.base_url, request))?;
+        if self.verify_pass_times {
+            iss_pass_times_from_json_checked(&data, request)
+        } else {
+            iss_pass_times_from_json(&data)
+        }
+    }
+
+    fn get(&self, url: &str) -> Result<String, OpenNotificationError> {
+        let mut attempt = 1;
+        loop {
+            let result = self.get_once(url);
+            match (result, self.retry.as_ref()) {
+                (Err(OpenNotificationError::CircuitOpen), _) => {
+                    return Err(OpenNotificationError::CircuitOpen)
+                }
+                (Err(ref e), Some(retry)) if e.is_transient() && attempt < retry.max_attempts() => {
+                    thread::sleep(retry.delay(attempt));
+                    attempt += 1;
+                }
+                (result, _) => return result,
+            }
+        }
+    }
+
+    fn get_once(&self, url: &str) -> Result<String, OpenNotificationError> {
+        if let Some(ref breaker) = self.circuit_breaker {
+            if !breaker.allow() {
+                return Err(OpenNotificationError::CircuitOpen);
+            }
+        }
+
+        let result = self
+            .transport
+            .get(url)
+            .and_then(|response| response_body(&response));
+
+        if let Some(ref breaker) = self.circuit_breaker {
+            match result {
+                Err(ref e) if e.is_transient() => breaker.record_failure(),
+                _ => breaker.record_success(),
+            }
+        }
+        result
+    }
+}
+
+/// Builder for `OpenNotifyClient`.
+///
+/// Timeout and user agent only apply to the transport created by
+/// `build`. A transport passed to `build_with_transport` is used
+/// as is. Retries only apply to the blocking client, the circuit
+/// breaker to both.
+pub struct OpenNotifyClientBuilder {
+    base_url: String,
+    verify_pass_times: bool,
+    retry: Option<RetryPolicy>,
+    circuit_breaker: Option<CircuitBreaker>,
+    #[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
+    timeout: Option<Duration>,
+    #[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
+    user_agent: Option<String>,
+}
+
+impl OpenNotifyClientBuilder {
+    pub fn new() -> OpenNotifyClientBuilder {
+        OpenNotifyClientBuilder {
+            base_url: String::from(DEFAULT_BASE_URL),
+            verify_pass_times: false,
+            retry: None,
+            circuit_breaker: None,
+            timeout: None,
+            user_agent: None,
+        }
+    }
+
+    /// Url of the server providing the open-notify endpoints,
+    /// e.g. a mirror or a local stand-in.
+    pub fn base_url(mut self, base_url: &str) -> OpenNotifyClientBuilder {
+        self.base_url = String::from(base_url.trim_end_matches('/'));
+        self
+    }
+
+    /// Check pass times responses against the request, see
+    /// [`iss_pass_times_from_json_checked`](../fn.iss_pass_times_from_json_checked.html).
+    /// Disabled by default.
+    pub fn verify_pass_times(mut self, verify: bool) -> OpenNotifyClientBuilder {
+        self.verify_pass_times = verify;
+        self
+    }
+
+    /// Repeat requests failing with a transient error as described
+    /// by `policy`. Requests are not repeated by default.
+    ///
+    /// Only applies to `build` and `build_with_transport`. Async
+    /// clients never repeat requests.
+    pub fn retry(mut self, policy: RetryPolicy) -> OpenNotifyClientBuilder {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Fail fast while open-notify is down, see `CircuitBreaker`.
+    pub fn circuit_breaker(mut self, breaker: CircuitBreaker) -> OpenNotifyClientBuilder {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Timeout applied to each request.
+    pub fn timeout(mut self, timeout: Duration) -> OpenNotifyClientBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Value of the `User-Agent` header sent with each request.
+    pub fn user_agent(mut self, user_agent: &str) -> OpenNotifyClientBuilder {
+        self.user_agent = Some(String::from(user_agent));
+        self
+    }
+
+    /// Creates a client backed by a `ReqwestTransport`.
+    #[cfg(feature = "reqwest")]
+    pub fn build(self) -> Result<OpenNotifyClient<ReqwestTransport>, OpenNotificationError> {
+        let transport = ReqwestTransport::with_options(self.timeout, self.user_agent.as_deref())?;
+        Ok(self.build_with_transport(transport))
+    }
+
+    /// Creates a client issuing its requests through `transport`.
+    pub fn build_with_transport<T: Transport>(self, transport: T) -> OpenNotifyClient<T> {
+        OpenNotifyClient {
+            base_url: self.base_url,
+            verify_pass_times: self.verify_pass_times,
+            retry: self.retry,
+            circuit_breaker: self.circuit_breaker.map(Arc::new),
+            transport,
+        }
+    }
+
+    /// Creates an async client backed by an `AsyncReqwestTransport`.
+    #[cfg(all(feature = "async", feature = "reqwest"))]
+    pub fn build_async(
+        self,
+    ) -> Result<AsyncOpenNotifyClient<AsyncReqwestTransport>, OpenNotificationError> {
+        let transport =
+            AsyncReqwestTransport::with_options(self.timeout, self.user_agent.as_deref())?;
+        Ok(self.build_async_with_transport(transport))
+    }
+
+    /// Creates an async client issuing its requests through `transport`.
+    #[cfg(feature = "async")]
+    pub fn build_async_with_transport<T: AsyncTransport>(
+        self,
+        transport: T,
+    ) -> AsyncOpenNotifyClient<T> {
+        AsyncOpenNotifyClient::from_parts(
+            self.base_url,
+            self.verify_pass_times,
+            self.circuit_breaker.map(Arc::new),
+            transport,
+        )
+    }
+}
+
+/// Body of a successful response, `OpenNotificationError::Http`
+/// for any other status.
+pub(crate) fn response_body(response: &Response) -> Result<String, OpenNotificationError> {
+    if (200..300).contains(&response.status()) {
+        Ok(String::from(response.body()))
+    } else {
+        Err(OpenNotificationError::http(
+            response.status(),
+            response.body(),
+        ))
+    }
+}
+
+pub(crate) fn endpoint_url(base_url: &str, endpoint: &str) -> String {
+    format!("{}/{}", base_url, endpoint)
+}
+
+pub(crate) fn iss_pass_times_url(base_url: &str, request: &IssPassRequest) -> String {
+    format!(
+        "{}?{}",
+        endpoint_url(base_url, "iss-pass.json"),
+        request.query()
+    )
+}
+
+impl Default for OpenNotifyClientBuilder {
+    fn default() -> OpenNotifyClientBuilder {
+        OpenNotifyClientBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use transport::MemoryTransport;
+
+    #[test]
+    fn client_default_base_url() {
+        let client = OpenNotifyClient::with_transport(MemoryTransport::new());
+        assert_eq!(client.base_url(), DEFAULT_BASE_URL);
+        assert_eq!(
+            endpoint_url(client.base_url(), "astros.json"),
+            "http://api.open-notify.org/astros.json"
+        );
+    }
+
+    #[test]
+    fn client_custom_base_url() {
+        let client = OpenNotifyClientBuilder::new()
+            .base_url("http://localhost:8080/mirror/")
+            .build_with_transport(MemoryTransport::new());
+        assert_eq!(client.base_url(), "http://localhost:8080/mirror");
+        assert_eq!(
+            endpoint_url(client.base_url(), "iss-now.json"),
+            "http://localhost:8080/mirror/iss-now.json"
+        );
+    }
+
+    #[test]
+    fn client_iss_pass_times_url() {
+        let mut transport = MemoryTransport::new();
+        transport.insert(
+            "http://localhost/iss-pass.json?lat=52.5&lon=13.4&alt=10&n=1",
+            200,
+            r#"{
+            "message": "success",
+            "request": {"latitude": 52.5, "longitude": 13.4, "altitude": 10,
+                        "passes": 1, "datetime": 1522000000},
+            "response": [{"risetime": 1522003000, "duration": 600}]
+            }"#,
+        );
+
+        let client = OpenNotifyClientBuilder::new()
+            .base_url("http://localhost")
+            .build_with_transport(transport);
+
+        let pass_times = client.iss_pass_times(52.5, 13.4, 10.0, 1).unwrap();
+        assert_eq!(pass_times.passes().len(), 1);
+        assert_eq!(pass_times.passes()[0].rise(), 1522003000);
+        assert_eq!(pass_times.passes()[0].duration(), 600);
+    }
+
+    #[test]
+    fn client_iss_pass_times_invalid_argument() {
+        let client = OpenNotifyClient::with_transport(MemoryTransport::new());
+
+        match client.iss_pass_times(85.0, 13.4, 10.0, 1) {
+            Err(OpenNotificationError::InvalidArgument(_)) => {}
+            _ => panic!(),
+        }
+        assert!(client.transport().requests().is_empty());
+    }
+
+    #[test]
+    fn client_verify_pass_times() {
+        let mut transport = MemoryTransport::new();
+        transport.insert(
+            "http://localhost/iss-pass.json?lat=52.5&lon=13.4&alt=10&n=2",
+            200,
+            r#"{
+            "message": "success",
+            "request": {"latitude": 52.5, "longitude": 13.4, "altitude": 10,
+                        "passes": 2, "datetime": 1522000000},
+            "response": [{"risetime": 1522003000, "duration": 600}]
+            }"#,
+        );
+
+        let client = OpenNotifyClientBuilder::new()
+            .base_url("http://localhost")
+            .build_with_transport(&transport);
+        assert!(client.iss_pass_times(52.5, 13.4, 10.0, 2).is_ok());
+
+        let client = OpenNotifyClientBuilder::new()
+            .base_url("http://localhost")
+            .verify_pass_times(true)
+            .build_with_transport(&transport);
+        match client.iss_pass_times(52.5, 13.4, 10.0, 2) {
+            Err(OpenNotificationError::Data(_)) => {}
+            _ => panic!(),
+        }
+    }
+}
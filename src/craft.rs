@@ -0,0 +1,234 @@
+//! People in space grouped by the craft they are on.
+//!
+//! open-notify reports crafts as free text, which isn't always spelled
+//! the same way. `Craft` normalizes the names of stations and capsules,
+//! e.g. `"International Space Station"` to `ISS`, `"Tianhe"` to
+//! `Tiangong` and `"soyuz ms 8"` to `Soyuz MS-08`. Unknown names are
+//! only trimmed.
+//!
+//! # Example
+//! ```
+//! let astros = open_notify_api::astro_from_json(r#"{"message": "success", "number": 3, "people": [
+//!     {"name": "Anton Shkaplerov", "craft": "ISS"},
+//!     {"name": "Oleg Artemyev", "craft": "Soyuz MS-08"},
+//!     {"name": "Scott Tingle", "craft": "iss"}]}"#).unwrap();
+//!
+//! for crew in astros.crafts() {
+//!     println!("{}: {} people", crew.craft(), crew.len());
+//! }
+//! assert_eq!(astros.crew_of("ISS").len(), 2);
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use {Astros, Person};
+
+/// Name of a spacecraft or space station, normalized.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Craft {
+    name: String,
+}
+
+impl Craft {
+    pub fn new(name: &str) -> Craft {
+        Craft {
+            name: normalize(name),
+        }
+    }
+
+    /// Normalized name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<'a> From<&'a str> for Craft {
+    fn from(name: &'a str) -> Craft {
+        Craft::new(name)
+    }
+}
+
+impl fmt::Display for Craft {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// People on one craft.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Crew<'a> {
+    craft: Craft,
+    people: Vec<&'a Person>,
+}
+
+impl<'a> Crew<'a> {
+    pub fn craft(&self) -> &Craft {
+        &self.craft
+    }
+
+    pub fn people(&self) -> &[&'a Person] {
+        &self.people
+    }
+
+    /// Number of people on the craft.
+    pub fn len(&self) -> usize {
+        self.people.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.people.is_empty()
+    }
+}
+
+impl Person {
+    /// Craft the person is on, normalized.
+    pub fn craft_normalized(&self) -> Craft {
+        Craft::new(self.craft())
+    }
+}
+
+impl Astros {
+    /// Crafts with their crew, in order of their first listing.
+    pub fn crafts(&self) -> Vec<Crew<'_>> {
+        let mut crews: Vec<Crew> = Vec::new();
+        for person in self.people() {
+            let craft = person.craft_normalized();
+            match crews.iter_mut().find(|crew| crew.craft == craft) {
+                Some(crew) => crew.people.push(person),
+                None => crews.push(Crew {
+                    craft,
+                    people: vec![person],
+                }),
+            }
+        }
+        crews
+    }
+
+    /// People on `craft`, compared by normalized names.
+    pub fn crew_of(&self, craft: &str) -> Vec<&Person> {
+        let craft = Craft::new(craft);
+        self.people()
+            .iter()
+            .filter(|person| person.craft_normalized() == craft)
+            .collect()
+    }
+
+    /// Number of people per craft.
+    pub fn craft_counts(&self) -> BTreeMap<Craft, usize> {
+        let mut counts = BTreeMap::new();
+        for person in self.people() {
+            *counts.entry(person.craft_normalized()).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+fn normalize(name: &str) -> String {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let lower = name.to_lowercase();
+
+    match lower.as_str() {
+        "iss" | "international space station" => return String::from("ISS"),
+        "tiangong" | "tianhe" | "css" | "china space station" | "chinese space station" => {
+            return String::from("Tiangong")
+        }
+        _ => {}
+    }
+
+    if let Some(rest) = lower.strip_prefix("soyuz") {
+        if let Some((series, number, suffix)) = designation(rest) {
+            let number = if series == "MS" && number.len() < 2 {
+                format!("0{}", number)
+            } else {
+                number
+            };
+            return format!("Soyuz {}-{}{}", series, number, suffix);
+        }
+    }
+    if let Some(rest) = lower.strip_prefix("shenzhou") {
+        if let Some((series, number, suffix)) = designation(rest) {
+            if series.is_empty() && suffix.is_empty() {
+                return format!("Shenzhou-{}", number);
+            }
+        }
+    }
+    name
+}
+
+/// Splits a designation like ` ms-08` or `-tma 20m` into upper case
+/// series, number and suffix.
+fn designation(text: &str) -> Option<(String, String, String)> {
+    let text: String = text
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+    let digits = text.find(|c: char| c.is_ascii_digit())?;
+    let (series, rest) = text.split_at(digits);
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let (number, suffix) = rest.split_at(end);
+    let number = number.trim_start_matches('0');
+    let number = if number.is_empty() { "0" } else { number };
+
+    if series.chars().all(|c| c.is_ascii_alphabetic())
+        && suffix.chars().all(|c| c.is_ascii_alphabetic())
+    {
+        Some((
+            String::from(series),
+            String::from(number),
+            String::from(suffix),
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use astro_from_json;
+
+    #[test]
+    fn normalized_names() {
+        assert_eq!(Craft::new("ISS").name(), "ISS");
+        assert_eq!(Craft::new(" international  space station").name(), "ISS");
+        assert_eq!(Craft::new("Tianhe").name(), "Tiangong");
+        assert_eq!(Craft::new("Soyuz MS-08").name(), "Soyuz MS-08");
+        assert_eq!(Craft::new("soyuz ms 8").name(), "Soyuz MS-08");
+        assert_eq!(Craft::new("SOYUZ-MS-12").name(), "Soyuz MS-12");
+        assert_eq!(Craft::new("Soyuz TMA-20M").name(), "Soyuz TMA-20M");
+        assert_eq!(Craft::new("shenzhou 17").name(), "Shenzhou-17");
+        assert_eq!(Craft::new("Crew  Dragon").name(), "Crew Dragon");
+        assert_eq!(Craft::new("Soyuz").name(), "Soyuz");
+        assert_eq!(Craft::from("iss"), Craft::new("ISS"));
+    }
+
+    #[test]
+    fn crews() {
+        let astros = astro_from_json(
+            r#"{"message": "success", "number": 4, "people": [
+            {"name": "Anton Shkaplerov", "craft": "ISS"},
+            {"name": "Oleg Artemyev", "craft": "soyuz ms 8"},
+            {"name": "Scott Tingle", "craft": "International Space Station"},
+            {"name": "Andrew Feustel", "craft": "Soyuz MS-08"}]}"#,
+        )
+        .unwrap();
+
+        let crafts = astros.crafts();
+        assert_eq!(crafts.len(), 2);
+        assert_eq!(crafts[0].craft().name(), "ISS");
+        assert_eq!(crafts[0].len(), 2);
+        assert_eq!(crafts[1].people()[1].name(), "Andrew Feustel");
+
+        let crew = astros.crew_of("soyuz MS-08");
+        assert_eq!(crew.len(), 2);
+        assert!(astros.crew_of("Tiangong").is_empty());
+
+        let counts = astros.craft_counts();
+        assert_eq!(counts[&Craft::new("ISS")], 2);
+        assert_eq!(counts[&Craft::new("Soyuz MS-08")], 2);
+    }
+}
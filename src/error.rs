@@ -1,16 +1,105 @@
+use std::error::Error;
+use std::fmt;
+
+#[cfg(feature = "reqwest")]
 use reqwest;
 use serde_json;
 
+/// Number of characters of a response body kept in
+/// `OpenNotificationError::Http`.
+const BODY_SNIPPET_LEN: usize = 200;
+
 #[derive(Debug)]
 pub enum OpenNotificationError {
     /// Something went wrong while fetching the data.
-    Network(reqwest::Error),
+    /// Carries the error reported by the `Transport`.
+    Network(Box<dyn Error + Send + Sync>),
+
+    /// The server did not respond in time.
+    Timeout,
+
+    /// The server responded with an error status code.
+    /// Carries the code and the beginning of the body.
+    Http { status: u16, body: String },
 
     /// Unexpected message structure.
     Parsing(serde_json::Error),
 
-    /// Unexpected or inconsistent information is detected.
+    /// open-notify answered with `"message": "failure"`.
+    /// Carries the reason given by the server.
+    Upstream(String),
+
+    /// Unexpected or inconsistent information is detected,
+    /// e.g. a number of people not matching the list of people.
     Data(String),
+
+    /// A request parameter is out of the range accepted by the api.
+    /// No request has been sent.
+    InvalidArgument(String),
+
+    /// The circuit breaker is open after repeated failures.
+    /// No request has been sent.
+    CircuitOpen,
+}
+
+impl OpenNotificationError {
+    /// Error for a response with the unsuccessful `status`.
+    pub(crate) fn http(status: u16, body: &str) -> OpenNotificationError {
+        OpenNotificationError::Http {
+            status,
+            body: body.chars().take(BODY_SNIPPET_LEN).collect(),
+        }
+    }
+
+    /// Whether the same request may succeed later.
+    ///
+    /// True for network failures, timeouts, 5xx and 429 responses
+    /// and an open circuit breaker.
+    pub fn is_transient(&self) -> bool {
+        match *self {
+            OpenNotificationError::Network(_)
+            | OpenNotificationError::Timeout
+            | OpenNotificationError::CircuitOpen => true,
+            OpenNotificationError::Http { status, .. } => status >= 500 || status == 429,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for OpenNotificationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OpenNotificationError::Network(ref e) => write!(f, "network error: {}", e),
+            OpenNotificationError::Timeout => write!(f, "request timed out"),
+            OpenNotificationError::Http { status, ref body } if body.is_empty() => {
+                write!(f, "server responded with status {}", status)
+            }
+            OpenNotificationError::Http { status, ref body } => {
+                write!(f, "server responded with status {}: {}", status, body)
+            }
+            OpenNotificationError::Parsing(ref e) => write!(f, "unexpected response: {}", e),
+            OpenNotificationError::Upstream(ref reason) => {
+                write!(f, "open-notify reported a failure: {}", reason)
+            }
+            OpenNotificationError::Data(ref msg) => write!(f, "invalid data: {}", msg),
+            OpenNotificationError::InvalidArgument(ref msg) => {
+                write!(f, "invalid argument: {}", msg)
+            }
+            OpenNotificationError::CircuitOpen => {
+                write!(f, "circuit breaker is open, open-notify is unavailable")
+            }
+        }
+    }
+}
+
+impl Error for OpenNotificationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            OpenNotificationError::Network(ref e) => Some(&**e),
+            OpenNotificationError::Parsing(ref e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<serde_json::Error> for OpenNotificationError {
@@ -19,8 +108,44 @@ impl From<serde_json::Error> for OpenNotificationError {
     }
 }
 
+#[cfg(feature = "reqwest")]
 impl From<reqwest::Error> for OpenNotificationError {
     fn from(e: reqwest::Error) -> OpenNotificationError {
-        OpenNotificationError::Network(e)
+        if e.is_timeout() {
+            OpenNotificationError::Timeout
+        } else {
+            OpenNotificationError::Network(Box::new(e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_error() {
+        let e = OpenNotificationError::http(503, &"x".repeat(1000));
+        assert!(e.is_transient());
+        match e {
+            OpenNotificationError::Http { status, ref body } => {
+                assert_eq!(status, 503);
+                assert_eq!(body.len(), BODY_SNIPPET_LEN);
+            }
+            _ => panic!(),
+        }
+
+        let e = OpenNotificationError::http(404, "not found");
+        assert!(!e.is_transient());
+        assert_eq!(e.to_string(), "server responded with status 404: not found");
+    }
+
+    #[test]
+    fn transient_errors() {
+        assert!(OpenNotificationError::Timeout.is_transient());
+        assert!(OpenNotificationError::http(429, "").is_transient());
+        assert!(!OpenNotificationError::Upstream(String::from("x")).is_transient());
+        assert!(!OpenNotificationError::Data(String::from("x")).is_transient());
+        assert!(!OpenNotificationError::InvalidArgument(String::from("x")).is_transient());
     }
 }
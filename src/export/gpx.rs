@@ -0,0 +1,153 @@
+//! GPX 1.1 tracks for GPS tools.
+//!
+//! # Example
+//! ```
+//! use open_notify_api::export::gpx::Gpx;
+//!
+//! let samples: Vec<_> = [
+//!     r#"{"message": "success", "timestamp": 100, "iss_position": {"latitude": 10.5, "longitude": 20}}"#,
+//!     r#"{"message": "success", "timestamp": 110, "iss_position": {"latitude": 11, "longitude": 20.5}}"#,
+//! ].iter().map(|data| open_notify_api::iss_now_from_json(data).unwrap()).collect();
+//!
+//! let gpx = Gpx::new("ISS").altitude(408.0).track("Orbit", &samples).to_string();
+//! assert!(gpx.contains(r#"<trkpt lat="10.5" lon="20"><ele>408000</ele>"#));
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use super::{date_time, escape};
+use IssNow;
+
+/// GPX document collecting tracks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gpx {
+    name: String,
+    altitude: Option<f64>,
+    max_gap: Option<Duration>,
+    tracks: Vec<String>,
+}
+
+impl Gpx {
+    pub fn new(name: &str) -> Gpx {
+        Gpx {
+            name: String::from(name),
+            altitude: None,
+            max_gap: None,
+            tracks: Vec::new(),
+        }
+    }
+
+    /// Altitude in kilometers written as elevation of the points of
+    /// tracks added afterwards. `IssNow` has no altitude, so points
+    /// have no elevation by default.
+    pub fn altitude(mut self, altitude: f64) -> Gpx {
+        self.altitude = Some(altitude);
+        self
+    }
+
+    /// Starts a new track segment where samples of tracks added
+    /// afterwards are further apart than `max_gap`.
+    pub fn max_gap(mut self, max_gap: Duration) -> Gpx {
+        self.max_gap = Some(max_gap);
+        self
+    }
+
+    /// Adds a track through `samples` in any order. Samples with the
+    /// same timestamp are only used once.
+    pub fn track(mut self, name: &str, samples: &[IssNow]) -> Gpx {
+        let mut samples: Vec<&IssNow> = samples.iter().collect();
+        samples.sort_by_key(|s| s.timestamp());
+        samples.dedup_by_key(|s| s.timestamp());
+
+        let elevation = self
+            .altitude
+            .map(|altitude| format!("<ele>{}</ele>", altitude * 1000.0))
+            .unwrap_or_default();
+        let max_gap = self.max_gap.map(|gap| gap.as_secs() as i64);
+
+        let mut track = format!("<trk><name>{}</name><trkseg>", escape(name));
+        let mut previous: Option<i64> = None;
+        for sample in samples {
+            if let (Some(previous), Some(gap)) = (previous, max_gap) {
+                if sample.timestamp() - previous > gap {
+                    track.push_str("</trkseg>\n<trkseg>");
+                }
+            }
+            track.push_str(&format!(
+                "\n<trkpt lat=\"{}\" lon=\"{}\">{}<time>{}</time></trkpt>",
+                sample.latitude_f64(),
+                sample.longitude_f64(),
+                elevation,
+                date_time(sample.timestamp())
+            ));
+            previous = Some(sample.timestamp());
+        }
+        track.push_str("\n</trkseg></trk>");
+
+        self.tracks.push(track);
+        self
+    }
+}
+
+impl fmt::Display for Gpx {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            f,
+            r#"<gpx version="1.1" creator="open-notify-api" xmlns="http://www.topografix.com/GPX/1/1">"#
+        )?;
+        writeln!(
+            f,
+            "<metadata><name>{}</name></metadata>",
+            escape(&self.name)
+        )?;
+        for track in &self.tracks {
+            writeln!(f, "{}", track)?;
+        }
+        writeln!(f, "</gpx>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tests::iss_now;
+
+    #[test]
+    fn track_points() {
+        let samples = [
+            iss_now(10, -34.6445, 19.6219),
+            iss_now(0, -35.0, 19.0),
+            iss_now(10, -34.6445, 19.6219),
+        ];
+        let gpx = Gpx::new("ISS <live>")
+            .track("Orbit 1", &samples)
+            .to_string();
+
+        assert!(gpx.contains("<metadata><name>ISS &lt;live&gt;</name></metadata>"));
+        assert_eq!(gpx.matches("<trkpt").count(), 2);
+        assert!(gpx.find(r#"lat="-35""#) < gpx.find(r#"lat="-34.6445""#));
+        assert!(gpx.contains(
+            r#"<trkpt lat="-34.6445" lon="19.6219"><time>1970-01-01T00:00:10Z</time></trkpt>"#
+        ));
+        assert!(!gpx.contains("<ele>"));
+    }
+
+    #[test]
+    fn segments_split_at_gaps() {
+        let samples = [
+            iss_now(0, 0.0, 0.0),
+            iss_now(10, 0.0, 1.0),
+            iss_now(500, 0.0, 30.0),
+        ];
+        let gpx = Gpx::new("ISS")
+            .altitude(408.0)
+            .max_gap(Duration::from_secs(60))
+            .track("Orbit", &samples)
+            .to_string();
+
+        assert_eq!(gpx.matches("<trkseg>").count(), 2);
+        assert_eq!(gpx.matches("<ele>408000</ele>").count(), 3);
+    }
+}
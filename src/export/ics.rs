@@ -0,0 +1,317 @@
+//! iCalendar ([RFC 5545](https://tools.ietf.org/html/rfc5545))
+//! calendars of ISS passes.
+//!
+//! Each pass becomes an event from rise to set. UIDs are derived from
+//! the rise time and the location, so a calendar regenerated from a
+//! fresh `IssPassTimes` updates existing events in subscribed
+//! calendars instead of duplicating them. Replies without the echoed
+//! request carry no location, their events have neither `GEO` nor a
+//! default `LOCATION`.
+//!
+//! # Example
+//! ```
+//! use std::time::Duration;
+//! use open_notify_api::export::ics::Calendar;
+//!
+//! let passes = open_notify_api::iss_pass_times_from_json(
+//!     r#"{"message": "success",
+//!     "request": {"altitude": 100, "datetime": 1522012140, "latitude": 52.5, "longitude": 13.4, "passes": 1},
+//!     "response": [{"duration": 496, "risetime": 1522015000}]}"#,
+//! ).unwrap();
+//!
+//! let calendar = Calendar::new("ISS passes over the office")
+//!     .location("Office, Berlin")
+//!     .alarm(Duration::from_secs(15 * 60))
+//!     .passes(&passes)
+//!     .to_string();
+//! assert!(calendar.contains("DTSTART:20180325T215640Z\r\n"));
+//! ```
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::date_time;
+use {coordinate, IssPassTimes};
+
+/// Longest line in octets before it is folded.
+const MAX_LINE: usize = 75;
+
+/// Calendar collecting pass events.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Calendar {
+    name: String,
+    location: Option<String>,
+    alarm: Option<Duration>,
+    stamp: i64,
+    events: Vec<Vec<String>>,
+}
+
+impl Calendar {
+    pub fn new(name: &str) -> Calendar {
+        Calendar {
+            name: String::from(name),
+            location: None,
+            alarm: None,
+            stamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs() as i64),
+            events: Vec::new(),
+        }
+    }
+
+    /// Location shown for events added afterwards. Defaults to the
+    /// coordinates of the observer, if the reply echoes them.
+    pub fn location(mut self, location: &str) -> Calendar {
+        self.location = Some(String::from(location));
+        self
+    }
+
+    /// Unix timestamp written as `DTSTAMP` of events added afterwards.
+    /// Defaults to the time the calendar was created.
+    pub fn stamp(mut self, timestamp: i64) -> Calendar {
+        self.stamp = timestamp;
+        self
+    }
+
+    /// Reminds `before` each rise for events added afterwards.
+    pub fn alarm(mut self, before: Duration) -> Calendar {
+        self.alarm = Some(before);
+        self
+    }
+
+    /// Adds an event for each pass of `passes`.
+    pub fn passes(mut self, passes: &IssPassTimes) -> Calendar {
+        let observer = if passes.has_request() {
+            Some((
+                coordinate(passes.latitude()),
+                coordinate(passes.longitude()),
+            ))
+        } else {
+            None
+        };
+        let location = self.location.clone().or_else(|| {
+            observer.map(|(latitude, longitude)| format!("{}, {}", latitude, longitude))
+        });
+
+        for pass in passes.passes() {
+            let uid = match observer {
+                Some((latitude, longitude)) => format!(
+                    "UID:iss-pass-{}-{}-{}@open-notify-api",
+                    pass.rise(),
+                    latitude,
+                    longitude
+                ),
+                None => format!("UID:iss-pass-{}@open-notify-api", pass.rise()),
+            };
+            let mut event = vec![
+                String::from("BEGIN:VEVENT"),
+                uid,
+                format!("DTSTAMP:{}", basic_date_time(self.stamp)),
+                format!("DTSTART:{}", basic_date_time(pass.rise())),
+                format!("DTEND:{}", basic_date_time(pass.set_time())),
+                String::from("SUMMARY:ISS pass"),
+                format!(
+                    "DESCRIPTION:{}",
+                    escape(&format!(
+                        "The ISS is above the horizon for {} s.",
+                        pass.duration()
+                    ))
+                ),
+            ];
+            if let Some(ref location) = location {
+                event.push(format!("LOCATION:{}", escape(location)));
+            }
+            if let Some((latitude, longitude)) = observer {
+                event.push(format!("GEO:{};{}", latitude, longitude));
+            }
+            event.push(String::from("TRANSP:TRANSPARENT"));
+            if let Some(before) = self.alarm {
+                event.extend(vec![
+                    String::from("BEGIN:VALARM"),
+                    String::from("ACTION:DISPLAY"),
+                    String::from("DESCRIPTION:ISS pass"),
+                    format!("TRIGGER:-PT{}S", before.as_secs()),
+                    String::from("END:VALARM"),
+                ]);
+            }
+            event.push(String::from("END:VEVENT"));
+            self.events.push(event);
+        }
+        self
+    }
+}
+
+impl fmt::Display for Calendar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let header = [
+            String::from("BEGIN:VCALENDAR"),
+            String::from("VERSION:2.0"),
+            String::from("PRODID:-//open-notify-api//ISS passes//EN"),
+            String::from("CALSCALE:GREGORIAN"),
+            format!("X-WR-CALNAME:{}", escape(&self.name)),
+        ];
+        for line in header.iter().chain(self.events.iter().flatten()) {
+            write!(f, "{}\r\n", fold(line))?;
+        }
+        write!(f, "END:VCALENDAR\r\n")
+    }
+}
+
+/// Unix `timestamp` in the basic UTC format, e.g. `20180325T212900Z`.
+fn basic_date_time(timestamp: i64) -> String {
+    date_time(timestamp).replace(['-', ':'], "")
+}
+
+/// Escapes a TEXT value.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Folds `line` into lines of at most 75 octets, continuation lines
+/// starting with a space. Never splits a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iss_pass_times_from_json;
+
+    fn passes() -> IssPassTimes {
+        iss_pass_times_from_json(
+            r#"{"message": "success",
+            "request": {"altitude": 100, "datetime": 1522012140, "latitude": 52.5, "longitude": 13.4, "passes": 2},
+            "response": [{"duration": 496, "risetime": 1522015000}, {"duration": 633, "risetime": 1522020700}]}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn pass_events() {
+        let calendar = Calendar::new("ISS, Berlin")
+            .stamp(1522012140)
+            .passes(&passes())
+            .to_string();
+
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(calendar.contains("X-WR-CALNAME:ISS\\, Berlin\r\n"));
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 2);
+        assert!(calendar.contains("UID:iss-pass-1522015000-52.5-13.4@open-notify-api\r\n"));
+        assert!(calendar.contains("DTSTAMP:20180325T210900Z\r\n"));
+        assert!(calendar.contains("DTSTART:20180325T215640Z\r\nDTEND:20180325T220456Z\r\n"));
+        assert!(calendar.contains("LOCATION:52.5\\, 13.4\r\nGEO:52.5;13.4\r\n"));
+        assert!(!calendar.contains("VALARM"));
+        assert!(calendar.lines().all(|line| line.len() <= MAX_LINE + 1));
+    }
+
+    #[test]
+    fn generation_stamp() {
+        let before = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let calendar = Calendar::new("ISS").passes(&passes()).to_string();
+        let stamp = calendar
+            .lines()
+            .find(|line| line.starts_with("DTSTAMP:"))
+            .unwrap();
+
+        assert!(stamp >= &*format!("DTSTAMP:{}", basic_date_time(before)));
+        assert!(!calendar.contains("DTSTAMP:20180325T210900Z"));
+    }
+
+    #[test]
+    fn reply_without_request() {
+        let passes = iss_pass_times_from_json(
+            r#"{"message": "success",
+            "response": [{"duration": 496, "risetime": 1522015000}]}"#,
+        )
+        .unwrap();
+        let calendar = Calendar::new("ISS")
+            .stamp(1522012140)
+            .passes(&passes)
+            .to_string();
+
+        assert!(calendar.contains("UID:iss-pass-1522015000@open-notify-api\r\n"));
+        assert!(calendar.contains("DTSTAMP:20180325T210900Z\r\n"));
+        assert!(calendar.contains("DTSTART:20180325T215640Z\r\n"));
+        assert!(!calendar.contains("GEO:"));
+        assert!(!calendar.contains("LOCATION:"));
+        assert!(!calendar.contains("19700101"));
+
+        let calendar = Calendar::new("ISS")
+            .location("Office")
+            .passes(&passes)
+            .to_string();
+        assert!(calendar.contains("LOCATION:Office\r\n"));
+        assert!(!calendar.contains("GEO:"));
+    }
+
+    #[test]
+    fn stable_uids() {
+        let uids = |calendar: String| -> Vec<String> {
+            calendar
+                .lines()
+                .filter(|line| line.starts_with("UID:"))
+                .map(String::from)
+                .collect()
+        };
+        assert_eq!(
+            uids(Calendar::new("A").passes(&passes()).to_string()),
+            uids(
+                Calendar::new("B")
+                    .location("Office")
+                    .passes(&passes())
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn alarms() {
+        let calendar = Calendar::new("ISS")
+            .location("Office; 3rd floor")
+            .alarm(Duration::from_secs(900))
+            .passes(&passes())
+            .to_string();
+
+        assert_eq!(calendar.matches("BEGIN:VALARM").count(), 2);
+        assert!(calendar.contains("TRIGGER:-PT900S\r\n"));
+        assert!(calendar.contains("LOCATION:Office\\; 3rd floor\r\n"));
+    }
+
+    #[test]
+    fn folding() {
+        let line = format!("DESCRIPTION:{}", "ä".repeat(60));
+        let folded = fold(&line);
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+        assert!(parts.len() > 1);
+        assert!(parts.iter().all(|part| part.len() <= MAX_LINE));
+        assert!(parts[1..].iter().all(|part| part.starts_with(' ')));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+        assert_eq!(fold("SUMMARY:ISS pass"), "SUMMARY:ISS pass");
+    }
+}
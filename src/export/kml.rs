@@ -0,0 +1,250 @@
+//! KML documents for Google Earth.
+//!
+//! # Example
+//! ```
+//! use open_notify_api::export::kml::Kml;
+//!
+//! let iss_now = open_notify_api::iss_now_from_json(
+//!     r#"{"message": "success", "timestamp": 1522012140,
+//!     "iss_position": {"latitude": -34.6445, "longitude": 19.6219}}"#,
+//! ).unwrap();
+//!
+//! let kml = Kml::new("ISS").position(&iss_now).to_string();
+//! assert!(kml.contains("<when>2018-03-25T21:09:00Z</when>"));
+//! ```
+
+use std::fmt;
+
+use super::{date_time, escape};
+use ground_track::GroundTrack;
+use predict::{Observer, PredictedPass};
+use {coordinate, IssNow, IssPassTimes};
+
+const STYLES: &str = r#"<Style id="iss"><IconStyle><scale>1.2</scale><Icon><href>http://maps.google.com/mapfiles/kml/shapes/star.png</href></Icon></IconStyle></Style>
+<Style id="track"><LineStyle><color>ff00d7ff</color><width>2</width></LineStyle></Style>
+<Style id="pass"><IconStyle><color>ff9e9e9e</color><Icon><href>http://maps.google.com/mapfiles/kml/paddle/wht-circle.png</href></Icon></IconStyle></Style>
+<Style id="visible-pass"><IconStyle><color>ff00ff00</color><Icon><href>http://maps.google.com/mapfiles/kml/paddle/grn-stars.png</href></Icon></IconStyle></Style>
+"#;
+
+/// KML document collecting placemarks.
+///
+/// Positions use the style `#iss`, tracks `#track`, passes `#pass`
+/// and predicted passes visible to the naked eye `#visible-pass`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kml {
+    name: String,
+    placemarks: Vec<String>,
+}
+
+impl Kml {
+    pub fn new(name: &str) -> Kml {
+        Kml {
+            name: String::from(name),
+            placemarks: Vec::new(),
+        }
+    }
+
+    /// Adds a placemark at the position of `iss_now`, stamped with its
+    /// time so Google Earth can animate a series of them.
+    pub fn position(mut self, iss_now: &IssNow) -> Kml {
+        self.placemarks.push(format!(
+            "<Placemark><name>ISS</name><TimeStamp><when>{}</when></TimeStamp>\
+             <styleUrl>#iss</styleUrl><Point><coordinates>{},{}</coordinates></Point></Placemark>",
+            date_time(iss_now.timestamp()),
+            iss_now.longitude_f64(),
+            iss_now.latitude_f64()
+        ));
+        self
+    }
+
+    /// Adds a placemark for each of `samples`.
+    pub fn positions(self, samples: &[IssNow]) -> Kml {
+        samples
+            .iter()
+            .fold(self, |kml, sample| kml.position(sample))
+    }
+
+    /// Adds `track` as a 3D line `altitude` kilometers above ground,
+    /// spanning the time of the track.
+    pub fn track(mut self, track: &GroundTrack, altitude: f64) -> Kml {
+        let (start, end) = match (track.points().next(), track.points().last()) {
+            (Some(start), Some(end)) => (start.timestamp(), end.timestamp()),
+            _ => return self,
+        };
+
+        let meters = altitude * 1000.0;
+        let lines: String = track
+            .segments()
+            .iter()
+            .map(|segment| {
+                let coordinates: Vec<String> = segment
+                    .iter()
+                    .map(|p| format!("{},{},{}", p.longitude(), p.latitude(), meters))
+                    .collect();
+                format!(
+                    "<LineString><altitudeMode>absolute</altitudeMode>\
+                     <coordinates>{}</coordinates></LineString>",
+                    coordinates.join(" ")
+                )
+            })
+            .collect();
+
+        self.placemarks.push(format!(
+            "<Placemark><name>Ground track</name>\
+             <TimeSpan><begin>{}</begin><end>{}</end></TimeSpan>\
+             <styleUrl>#track</styleUrl><MultiGeometry>{}</MultiGeometry></Placemark>",
+            date_time(start),
+            date_time(end),
+            lines
+        ));
+        self
+    }
+
+    /// Adds a marker at the observer for each pass of `passes`.
+    pub fn passes(mut self, passes: &IssPassTimes) -> Kml {
+        let location = format!(
+            "{},{}",
+            coordinate(passes.longitude()),
+            coordinate(passes.latitude())
+        );
+        for pass in passes.passes() {
+            self.placemarks.push(pass_placemark(
+                pass.rise(),
+                pass.set_time(),
+                &format!("Duration {} s", pass.duration()),
+                "#pass",
+                &location,
+            ));
+        }
+        self
+    }
+
+    /// Adds a marker at `observer` for each of `passes`, highlighting
+    /// passes visible to the naked eye.
+    pub fn predicted_passes(mut self, observer: &Observer, passes: &[PredictedPass]) -> Kml {
+        let location = format!("{},{}", observer.longitude(), observer.latitude());
+        for pass in passes {
+            let style = if pass.is_visible() {
+                "#visible-pass"
+            } else {
+                "#pass"
+            };
+            let description = format!(
+                "Duration {} s, maximal elevation {:.0}°, rises at {:.0}°, sets at {:.0}°{}",
+                pass.duration(),
+                pass.max_elevation(),
+                pass.rise_azimuth(),
+                pass.set_azimuth(),
+                if pass.is_visible() { ", visible" } else { "" }
+            );
+            self.placemarks.push(pass_placemark(
+                pass.rise(),
+                pass.set_time(),
+                &description,
+                style,
+                &location,
+            ));
+        }
+        self
+    }
+}
+
+impl fmt::Display for Kml {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(f, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+        writeln!(f, "<Document><name>{}</name>", escape(&self.name))?;
+        write!(f, "{}", STYLES)?;
+        for placemark in &self.placemarks {
+            writeln!(f, "{}", placemark)?;
+        }
+        writeln!(f, "</Document>")?;
+        writeln!(f, "</kml>")
+    }
+}
+
+fn pass_placemark(rise: i64, set: i64, description: &str, style: &str, location: &str) -> String {
+    format!(
+        "<Placemark><name>Pass {}</name><description>{}</description>\
+         <TimeSpan><begin>{}</begin><end>{}</end></TimeSpan>\
+         <styleUrl>{}</styleUrl><Point><coordinates>{}</coordinates></Point></Placemark>",
+        date_time(rise),
+        escape(description),
+        date_time(rise),
+        date_time(set),
+        style,
+        location
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iss_pass_times_from_json;
+    use orbit::tests::iss_sgp4;
+    use predict::PassPredictor;
+    use std::time::Duration;
+    use tests::iss_now;
+
+    #[test]
+    fn document() {
+        let kml = Kml::new("Tom & Jerry").to_string();
+        assert!(kml.starts_with("<?xml"));
+        assert!(kml.contains("<name>Tom &amp; Jerry</name>"));
+        assert!(kml.contains(r#"<Style id="visible-pass">"#));
+        assert!(kml.trim_end().ends_with("</kml>"));
+    }
+
+    #[test]
+    fn positions_and_track() {
+        let samples = [iss_now(0, 10.0, 179.0), iss_now(10, 11.0, -179.0)];
+        let track = GroundTrack::from_history(&samples, Duration::from_secs(60));
+        let kml = Kml::new("ISS")
+            .positions(&samples)
+            .track(&track, 408.0)
+            .track(&GroundTrack::default(), 408.0)
+            .to_string();
+
+        assert_eq!(kml.matches("<Placemark>").count(), 3);
+        assert!(kml.contains("<when>1970-01-01T00:00:10Z</when>"));
+        assert!(kml.contains("<coordinates>-179,11</coordinates>"));
+        assert!(kml.contains("<begin>1970-01-01T00:00:00Z</begin><end>1970-01-01T00:00:10Z</end>"));
+        assert_eq!(kml.matches("<LineString>").count(), 2);
+        assert!(kml.contains("<coordinates>179,10,408000 180,10.5,408000</coordinates>"));
+    }
+
+    #[test]
+    fn pass_markers() {
+        let passes = iss_pass_times_from_json(
+            r#"{"message": "success",
+            "request": {"altitude": 100, "datetime": 1522012140, "latitude": 52.5, "longitude": 13.4, "passes": 1},
+            "response": [{"duration": 496, "risetime": 1522015000}]}"#,
+        )
+        .unwrap();
+        let kml = Kml::new("Passes").passes(&passes).to_string();
+
+        assert!(kml.contains("<styleUrl>#pass</styleUrl>"));
+        assert!(kml.contains("<description>Duration 496 s</description>"));
+        assert!(kml.contains("<coordinates>13.4,52.5</coordinates>"));
+        assert!(kml.contains("<end>2018-03-25T22:04:56Z</end>"));
+    }
+
+    #[test]
+    fn predicted_pass_markers() {
+        let sgp4 = iss_sgp4();
+        let observer = Observer::new(52.5, 13.4, 0.0).unwrap();
+        let passes = PassPredictor::new(&sgp4, observer)
+            .next_passes(1522012140, 3)
+            .unwrap();
+        let kml = Kml::new("Passes")
+            .predicted_passes(&observer, &passes)
+            .to_string();
+
+        assert_eq!(kml.matches("<Placemark>").count(), 3);
+        assert_eq!(
+            kml.matches("<styleUrl>#visible-pass</styleUrl>").count(),
+            passes.iter().filter(|p| p.is_visible()).count()
+        );
+        assert!(kml.contains("maximal elevation"));
+    }
+}
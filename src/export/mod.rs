@@ -0,0 +1,88 @@
+//! Exports for mapping and GPS tools, with the `export` feature.
+//!
+//! * `kml::Kml` builds KML documents for Google Earth with time
+//!   stamped positions, 3D ground tracks and pass markers.
+//! * `gpx::Gpx` builds GPX tracks from series of `IssNow` samples.
+//! * `ics::Calendar` builds iCalendar files of upcoming passes to
+//!   import into or subscribe to with calendar applications.
+//!
+//! All of them write plain text, no XML or calendar library is needed.
+
+pub mod gpx;
+pub mod ics;
+pub mod kml;
+
+/// Unix `timestamp` as an XML Schema `dateTime` in UTC,
+/// e.g. `2018-03-25T21:29:00Z`.
+fn date_time(timestamp: i64) -> String {
+    let (year, month, day) = civil_from_days(timestamp.div_euclid(86400));
+    let seconds = timestamp.rem_euclid(86400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Date of the proleptic Gregorian calendar the given number of
+/// days after 1970-01-01.
+fn civil_from_days(days: i64) -> (i32, u32, u32) {
+    let days = days + 719468;
+    let era = if days >= 0 { days } else { days - 146096 } / 146097;
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year as i32, month, day)
+}
+
+/// Escapes text for XML content and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(17615), (2018, 3, 25));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn date_times() {
+        assert_eq!(date_time(0), "1970-01-01T00:00:00Z");
+        assert_eq!(date_time(1522012140), "2018-03-25T21:09:00Z");
+        assert_eq!(date_time(-1), "1969-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(
+            escape(r#"Crew <"Soyuz" & 'ISS'>"#),
+            "Crew &lt;&quot;Soyuz&quot; &amp; &apos;ISS&apos;&gt;"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Great circle geometry between ISS positions and observers.
+//!
+//! Distances along the ground use a spherical earth with the mean
+//! earth radius, accurate to about 0.5%. Slant ranges use the WGS84
+//! ellipsoid, like the pass predictions.
+
+use orbit::geodetic_to_ecef;
+use predict::Observer;
+use IssNow;
+
+/// Mean earth radius in kilometers (IUGG).
+pub const MEAN_EARTH_RADIUS: f64 = 6371.0088;
+
+/// Great circle distance in kilometers between two points given by
+/// latitude and longitude in degrees.
+pub fn distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = phi2 - phi1;
+    let d_lambda = (lon2 - lon1).to_radians();
+
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    let a = a.clamp(0.0, 1.0);
+    2.0 * MEAN_EARTH_RADIUS * a.sqrt().atan2((1.0 - a).sqrt())
+}
+
+/// Initial bearing in degrees (0 to 360, clockwise from north) of the
+/// great circle from the first to the second point.
+pub fn bearing(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lambda = (lon2 - lon1).to_radians();
+
+    let y = d_lambda.sin() * phi2.cos();
+    let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * d_lambda.cos();
+    normalize(y.atan2(x).to_degrees())
+}
+
+/// Point reached when travelling `distance` kilometers from the given
+/// point along the great circle with initial `bearing` in degrees.
+pub fn destination(latitude: f64, longitude: f64, bearing: f64, distance: f64) -> (f64, f64) {
+    let phi1 = latitude.to_radians();
+    let theta = bearing.to_radians();
+    let delta = distance / MEAN_EARTH_RADIUS;
+
+    let phi2 = (phi1.sin() * delta.cos() + phi1.cos() * delta.sin() * theta.cos())
+        .clamp(-1.0, 1.0)
+        .asin();
+    let lambda =
+        (theta.sin() * delta.sin() * phi1.cos()).atan2(delta.cos() - phi1.sin() * phi2.sin());
+
+    let longitude = normalize(longitude + lambda.to_degrees() + 180.0) - 180.0;
+    (phi2.to_degrees(), longitude)
+}
+
+fn normalize(degrees: f64) -> f64 {
+    let degrees = degrees % 360.0;
+    if degrees < 0.0 {
+        degrees + 360.0
+    } else {
+        degrees
+    }
+}
+
+impl IssNow {
+    /// Great circle distance in kilometers between the sub-satellite
+    /// points of both samples.
+    pub fn distance_to(&self, other: &IssNow) -> f64 {
+        distance(
+            self.latitude_f64(),
+            self.longitude_f64(),
+            other.latitude_f64(),
+            other.longitude_f64(),
+        )
+    }
+
+    /// Initial bearing in degrees from this sample towards `other`.
+    pub fn bearing_to(&self, other: &IssNow) -> f64 {
+        bearing(
+            self.latitude_f64(),
+            self.longitude_f64(),
+            other.latitude_f64(),
+            other.longitude_f64(),
+        )
+    }
+
+    /// Direction of travel in degrees at this sample, coming from
+    /// the earlier sample `previous`.
+    pub fn heading(&self, previous: &IssNow) -> f64 {
+        normalize(self.bearing_to(previous) + 180.0)
+    }
+
+    /// Average speed of the sub-satellite point in kilometers per
+    /// second between both samples. `None` if they were taken at the
+    /// same time.
+    pub fn ground_speed(&self, other: &IssNow) -> Option<f64> {
+        let seconds = (other.timestamp() - self.timestamp()).abs();
+        if seconds == 0 {
+            None
+        } else {
+            Some(self.distance_to(other) / seconds as f64)
+        }
+    }
+}
+
+impl Observer {
+    /// Great circle distance in kilometers from the observer to the
+    /// point below the ISS.
+    pub fn ground_distance(&self, iss_now: &IssNow) -> f64 {
+        distance(
+            self.latitude(),
+            self.longitude(),
+            iss_now.latitude_f64(),
+            iss_now.longitude_f64(),
+        )
+    }
+
+    /// Direct distance in kilometers from the observer to the ISS
+    /// flying `altitude` kilometers above the point of `iss_now`.
+    ///
+    /// `iss_now.json` does not report an altitude. The ISS orbits at
+    /// about 400 to 420 km, `orbit::Sgp4` gives the exact value.
+    pub fn slant_range(&self, iss_now: &IssNow, altitude: f64) -> f64 {
+        let iss = geodetic_to_ecef(iss_now.latitude_f64(), iss_now.longitude_f64(), altitude);
+        self.look_angles(&iss).range
+    }
+}
+
+/// Area on the ground around a point from which an object flying at
+/// a given altitude above that point is seen above a minimal
+/// elevation. Equivalently, the area the point below the object has
+/// to be in to be seen from the center.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Footprint {
+    latitude: f64,
+    longitude: f64,
+    radius: f64,
+}
+
+impl Footprint {
+    /// Footprint around `latitude` and `longitude` in degrees for an
+    /// object at `altitude` kilometers seen at least `min_elevation`
+    /// degrees above the horizon.
+    pub fn new(latitude: f64, longitude: f64, altitude: f64, min_elevation: f64) -> Footprint {
+        let elevation = min_elevation.clamp(0.0, 90.0).to_radians();
+        let angle = (MEAN_EARTH_RADIUS * elevation.cos() / (MEAN_EARTH_RADIUS + altitude.max(0.0)))
+            .acos()
+            - elevation;
+        Footprint {
+            latitude,
+            longitude,
+            radius: angle.max(0.0) * MEAN_EARTH_RADIUS,
+        }
+    }
+
+    /// Area from which the ISS at `altitude` kilometers above the
+    /// point of `iss_now` is seen above `min_elevation` degrees.
+    pub fn of_iss(iss_now: &IssNow, altitude: f64, min_elevation: f64) -> Footprint {
+        Footprint::new(
+            iss_now.latitude_f64(),
+            iss_now.longitude_f64(),
+            altitude,
+            min_elevation,
+        )
+    }
+
+    /// Area the ISS at `altitude` kilometers has to be above to be
+    /// seen by `observer` above `min_elevation` degrees.
+    pub fn visible_from(observer: &Observer, altitude: f64, min_elevation: f64) -> Footprint {
+        Footprint::new(
+            observer.latitude(),
+            observer.longitude(),
+            altitude,
+            min_elevation,
+        )
+    }
+
+    /// Latitude of the center in degrees
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    /// Longitude of the center in degrees
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    /// Radius along the ground in kilometers.
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    /// Whether the point at `latitude` and `longitude` lies inside.
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        distance(self.latitude, self.longitude, latitude, longitude) <= self.radius
+    }
+
+    /// `points` points on the border, counterclockwise seen from
+    /// above, starting north of the center.
+    pub fn border(&self, points: usize) -> Vec<(f64, f64)> {
+        (0..points)
+            .map(|i| {
+                let bearing = 360.0 - 360.0 * i as f64 / points as f64;
+                destination(self.latitude, self.longitude, bearing, self.radius)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tests::iss_now;
+
+    #[test]
+    fn great_circle_distance() {
+        // Berlin to Paris
+        assert!((distance(52.52, 13.405, 48.8566, 2.3522) - 877.5).abs() < 1.0);
+        // Across the antimeridian
+        assert!((distance(0.0, 179.5, 0.0, -179.5) - 111.195).abs() < 0.01);
+        // Over the pole
+        assert!((distance(89.0, 0.0, 89.0, 180.0) - 222.39).abs() < 0.01);
+        assert_eq!(distance(10.0, 20.0, 10.0, 20.0), 0.0);
+    }
+
+    #[test]
+    fn bearings() {
+        assert!((bearing(0.0, 0.0, 0.0, 1.0) - 90.0).abs() < 1e-9);
+        assert!((bearing(0.0, 0.0, 1.0, 0.0)).abs() < 1e-9);
+        assert!((bearing(0.0, 179.5, 0.0, -179.5) - 90.0).abs() < 1e-9);
+        assert!((bearing(0.0, 0.0, -1.0, 0.0) - 180.0).abs() < 1e-9);
+        assert!((bearing(0.0, 0.0, 0.0, -1.0) - 270.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn iss_motion() {
+        let first = iss_now(1000, 0.0, 0.0);
+        let second = iss_now(1010, 0.5, 0.5);
+
+        assert!((first.distance_to(&second) - 78.63).abs() < 0.1);
+        assert!((first.bearing_to(&second) - 45.0).abs() < 0.1);
+        assert!((second.heading(&first) - 45.0).abs() < 0.1);
+        assert!((first.ground_speed(&second).unwrap() - 7.863).abs() < 0.01);
+        assert!(first.ground_speed(&first).is_none());
+    }
+
+    #[test]
+    fn destinations() {
+        let (lat, lon) = destination(0.0, 179.5, 90.0, 111.195);
+        assert!(lat.abs() < 1e-6);
+        assert!((lon + 179.5).abs() < 1e-4);
+
+        let (lat, lon) = destination(52.5, 13.4, 123.0, 500.0);
+        assert!((distance(52.5, 13.4, lat, lon) - 500.0).abs() < 1e-6);
+        assert!((bearing(52.5, 13.4, lat, lon) - 123.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn footprints() {
+        // Horizon as seen from the ISS
+        let footprint = Footprint::new(0.0, 0.0, 408.0, 0.0);
+        assert!((footprint.radius() - 2221.6).abs() < 0.1);
+
+        let footprint = Footprint::new(52.5, 13.4, 408.0, 10.0);
+        assert!((footprint.radius() - 1362.2).abs() < 0.1);
+        assert!(footprint.contains(48.8566, 2.3522));
+        assert!(!footprint.contains(40.4, -3.7));
+        for (lat, lon) in footprint.border(16) {
+            assert!((distance(52.5, 13.4, lat, lon) - footprint.radius()).abs() < 1e-6);
+        }
+
+        assert_eq!(Footprint::new(0.0, 0.0, 408.0, 90.0).radius(), 0.0);
+    }
+
+    #[test]
+    fn observer_distances() {
+        let observer = Observer::new(52.5, 13.4, 0.0).unwrap();
+
+        let overhead = iss_now(0, 52.5, 13.4);
+        assert!(observer.ground_distance(&overhead) < 1e-3);
+        assert!((observer.slant_range(&overhead, 408.0) - 408.0).abs() < 1e-3);
+
+        let away = iss_now(0, 52.5, 23.4);
+        let ground = observer.ground_distance(&away);
+        let slant = observer.slant_range(&away, 408.0);
+        assert!((ground - 676.0).abs() < 2.0);
+        assert!(slant > ground && slant < ground + 408.0);
+    }
+}
@@ -0,0 +1,357 @@
+//! GeoJSON ([RFC 7946](https://tools.ietf.org/html/rfc7946)) features
+//! for web maps and GIS tools like Leaflet, Mapbox or QGIS.
+//!
+//! * `IssNow` becomes a `Point` with a `timestamp` property.
+//! * `GroundTrack` becomes a `MultiLineString` with one line per
+//!   segment, split at the antimeridian, and `start` and `end`
+//!   properties.
+//! * `Footprint` becomes a `Polygon` with a `radius_km` property. A
+//!   footprint crossing the antimeridian or containing a pole is cut
+//!   along the antimeridian into a `MultiPolygon`.
+//!
+//! Coordinates are `[longitude, latitude]` in degrees and polygon
+//! rings run counterclockwise, as the RFC requires.
+//!
+//! # Example
+//! ```
+//! use open_notify_api::geo::Footprint;
+//! use open_notify_api::geojson::{feature_collection, ToGeoJson};
+//!
+//! let iss_now = open_notify_api::iss_now_from_json(
+//!     r#"{"message": "success", "timestamp": 1522012140,
+//!     "iss_position": {"latitude": -34.6445, "longitude": 19.6219}}"#,
+//! ).unwrap();
+//! let footprint = Footprint::of_iss(&iss_now, 408.0, 10.0);
+//!
+//! let map = feature_collection(vec![iss_now.to_geojson(), footprint.to_geojson()]);
+//! assert_eq!(map["features"][0]["geometry"]["coordinates"][0], 19.6219);
+//! ```
+
+use serde_json::{self, Map, Value};
+
+use geo::Footprint;
+use ground_track::GroundTrack;
+use IssNow;
+
+/// Number of points on the border of a footprint.
+const BORDER_POINTS: usize = 90;
+
+type Position = [f64; 2];
+
+#[derive(Serialize)]
+#[serde(tag = "type", content = "coordinates")]
+enum Geometry {
+    Point(Position),
+    MultiLineString(Vec<Vec<Position>>),
+    Polygon(Vec<Vec<Position>>),
+    MultiPolygon(Vec<Vec<Vec<Position>>>),
+}
+
+#[derive(Serialize)]
+struct Feature {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    geometry: Geometry,
+    properties: Map<String, Value>,
+}
+
+impl Feature {
+    fn new(geometry: Geometry, properties: Map<String, Value>) -> Feature {
+        Feature {
+            kind: "Feature",
+            geometry,
+            properties,
+        }
+    }
+
+    fn into_value(self) -> Value {
+        serde_json::to_value(self).expect("features serialize to JSON")
+    }
+}
+
+/// Conversion to a GeoJSON `Feature`.
+pub trait ToGeoJson {
+    fn to_geojson(&self) -> Value;
+
+    /// Feature as compact JSON text.
+    fn to_geojson_string(&self) -> String {
+        self.to_geojson().to_string()
+    }
+}
+
+/// `FeatureCollection` of `features`, e.g. from `ToGeoJson::to_geojson`.
+pub fn feature_collection<I: IntoIterator<Item = Value>>(features: I) -> Value {
+    let mut collection = Map::new();
+    collection.insert(String::from("type"), Value::from("FeatureCollection"));
+    collection.insert(
+        String::from("features"),
+        Value::Array(features.into_iter().collect()),
+    );
+    Value::Object(collection)
+}
+
+impl ToGeoJson for IssNow {
+    fn to_geojson(&self) -> Value {
+        let mut properties = Map::new();
+        properties.insert(String::from("timestamp"), Value::from(self.timestamp()));
+        Feature::new(
+            Geometry::Point([self.longitude_f64(), self.latitude_f64()]),
+            properties,
+        )
+        .into_value()
+    }
+}
+
+impl ToGeoJson for GroundTrack {
+    fn to_geojson(&self) -> Value {
+        let mut properties = Map::new();
+        if let Some(first) = self.points().next() {
+            properties.insert(String::from("start"), Value::from(first.timestamp()));
+        }
+        if let Some(last) = self.points().last() {
+            properties.insert(String::from("end"), Value::from(last.timestamp()));
+        }
+
+        let lines = self
+            .segments()
+            .iter()
+            .map(|segment| {
+                segment
+                    .iter()
+                    .map(|p| [p.longitude(), p.latitude()])
+                    .collect()
+            })
+            .collect();
+        Feature::new(Geometry::MultiLineString(lines), properties).into_value()
+    }
+}
+
+impl ToGeoJson for Footprint {
+    fn to_geojson(&self) -> Value {
+        let mut properties = Map::new();
+        properties.insert(String::from("radius_km"), Value::from(self.radius()));
+
+        let mut polygons = footprint_polygons(self);
+        let geometry = if polygons.len() == 1 {
+            Geometry::Polygon(polygons.remove(0))
+        } else {
+            Geometry::MultiPolygon(polygons)
+        };
+        Feature::new(geometry, properties).into_value()
+    }
+}
+
+/// Polygons covering the footprint, each with a single closed ring,
+/// with all longitudes between -180 and 180.
+fn footprint_polygons(footprint: &Footprint) -> Vec<Vec<Vec<Position>>> {
+    if footprint.radius() <= 0.0 {
+        return Vec::new();
+    }
+
+    // Continuous longitudes, possibly beyond ±180.
+    let mut ring: Vec<Position> = Vec::with_capacity(BORDER_POINTS + 3);
+    for (latitude, longitude) in footprint.border(BORDER_POINTS) {
+        let longitude = match ring.last() {
+            Some(last) => last[0] + wrap(longitude - last[0]),
+            None => longitude,
+        };
+        ring.push([longitude, latitude]);
+    }
+
+    // A ring around a pole doesn't close but ends a full turn away
+    // from its start, so it is closed over the pole.
+    let first = ring[0];
+    let last = ring[ring.len() - 1];
+    let end = last[0] + wrap(first[0] - last[0]);
+    if (end - first[0]).abs() > 180.0 {
+        let pole = if footprint.latitude() >= 0.0 {
+            90.0
+        } else {
+            -90.0
+        };
+        ring.push([end, first[1]]);
+        ring.push([end, pole]);
+        ring.push([first[0], pole]);
+    }
+
+    let parts = vec![
+        (clip(&clip(&ring, 180.0, false), -180.0, true), 0.0),
+        (clip(&ring, 180.0, true), -360.0),
+        (clip(&ring, -180.0, false), 360.0),
+    ];
+    parts
+        .into_iter()
+        .filter(|(part, _)| part.len() >= 3)
+        .map(|(part, shift)| {
+            let mut part: Vec<Position> = part.iter().map(|p| [p[0] + shift, p[1]]).collect();
+            let start = part[0];
+            part.push(start);
+            vec![part]
+        })
+        .collect()
+}
+
+/// Part of the `ring` east (`east` true) or west of `longitude`.
+fn clip(ring: &[Position], longitude: f64, east: bool) -> Vec<Position> {
+    let inside = |p: &Position| {
+        if east {
+            p[0] >= longitude
+        } else {
+            p[0] <= longitude
+        }
+    };
+
+    let mut clipped = Vec::new();
+    for (i, current) in ring.iter().enumerate() {
+        let previous = &ring[(i + ring.len() - 1) % ring.len()];
+        if inside(current) != inside(previous) {
+            let f = (longitude - previous[0]) / (current[0] - previous[0]);
+            let crossing = [longitude, previous[1] + f * (current[1] - previous[1])];
+            if clipped.last() != Some(&crossing) {
+                clipped.push(crossing);
+            }
+        }
+        if inside(current) && clipped.last() != Some(current) {
+            clipped.push(*current);
+        }
+    }
+    clipped
+}
+
+/// Longitude difference in degrees wrapped to -180 to 180.
+fn wrap(degrees: f64) -> f64 {
+    let degrees = (degrees + 180.0) % 360.0;
+    if degrees < 0.0 {
+        degrees + 180.0
+    } else {
+        degrees - 180.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tests::iss_now;
+
+    fn rings(feature: &Value) -> Vec<Vec<Position>> {
+        let geometry = &feature["geometry"];
+        let polygons: Vec<Vec<Vec<Position>>> = match geometry["type"].as_str().unwrap() {
+            "Polygon" => vec![serde_json::from_value(geometry["coordinates"].clone()).unwrap()],
+            "MultiPolygon" => serde_json::from_value(geometry["coordinates"].clone()).unwrap(),
+            other => panic!("unexpected geometry {}", other),
+        };
+        polygons.into_iter().map(|mut p| p.remove(0)).collect()
+    }
+
+    /// Twice the signed area, positive for counterclockwise rings.
+    fn signed_area(ring: &[Position]) -> f64 {
+        ring.windows(2)
+            .map(|w| w[0][0] * w[1][1] - w[1][0] * w[0][1])
+            .sum()
+    }
+
+    fn assert_valid(ring: &[Position]) {
+        assert_eq!(ring.first(), ring.last());
+        assert!(ring.len() >= 4);
+        assert!(signed_area(ring) > 0.0);
+        for p in ring {
+            assert!(p[0] >= -180.0 && p[0] <= 180.0);
+            assert!(p[1] >= -90.0 && p[1] <= 90.0);
+        }
+    }
+
+    #[test]
+    fn iss_now_point() {
+        let feature = iss_now(1522012140, -34.6445, 19.6219).to_geojson();
+        assert_eq!(feature["type"], "Feature");
+        assert_eq!(feature["geometry"]["type"], "Point");
+        assert_eq!(
+            feature["geometry"]["coordinates"],
+            serde_json::json!([19.6219, -34.6445])
+        );
+        assert_eq!(feature["properties"]["timestamp"], 1522012140);
+    }
+
+    #[test]
+    fn ground_track_lines() {
+        let samples = [
+            iss_now(0, 10.0, 179.0),
+            iss_now(10, 11.0, -179.0),
+            iss_now(20, 12.0, -178.0),
+        ];
+        let track = GroundTrack::from_history(&samples, Duration::from_secs(60));
+        let feature = track.to_geojson();
+
+        assert_eq!(feature["geometry"]["type"], "MultiLineString");
+        let lines = feature["geometry"]["coordinates"].as_array().unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0][1], serde_json::json!([180.0, 10.5]));
+        assert_eq!(lines[1][0], serde_json::json!([-180.0, 10.5]));
+        assert_eq!(feature["properties"]["start"], 0);
+        assert_eq!(feature["properties"]["end"], 20);
+
+        let empty = GroundTrack::default().to_geojson();
+        assert_eq!(empty["geometry"]["coordinates"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn footprint_polygon() {
+        let footprint = Footprint::new(52.5, 13.4, 408.0, 10.0);
+        let feature = footprint.to_geojson();
+        assert_eq!(feature["geometry"]["type"], "Polygon");
+        assert_eq!(feature["properties"]["radius_km"], footprint.radius());
+
+        let rings = rings(&feature);
+        assert_eq!(rings.len(), 1);
+        assert_eq!(rings[0].len(), BORDER_POINTS + 1);
+        assert_valid(&rings[0]);
+    }
+
+    #[test]
+    fn footprint_across_antimeridian() {
+        let footprint = Footprint::new(-10.0, 175.0, 408.0, 0.0);
+        let feature = footprint.to_geojson();
+        assert_eq!(feature["geometry"]["type"], "MultiPolygon");
+
+        let rings = rings(&feature);
+        assert_eq!(rings.len(), 2);
+        for ring in &rings {
+            assert_valid(ring);
+        }
+        assert!(rings[0].iter().any(|p| p[0] == 180.0));
+        assert!(rings[1].iter().any(|p| p[0] == -180.0));
+    }
+
+    #[test]
+    fn footprint_around_pole() {
+        for &latitude in &[80.0, -85.0] {
+            let feature = Footprint::new(latitude, 30.0, 408.0, 0.0).to_geojson();
+            let rings = rings(&feature);
+            let pole = latitude.signum() * 90.0;
+            for ring in &rings {
+                assert_valid(ring);
+                assert!(ring.iter().any(|p| p[1] == pole));
+            }
+            let width: f64 = rings
+                .iter()
+                .map(|ring| {
+                    let west = ring.iter().map(|p| p[0]).fold(180.0, f64::min);
+                    let east = ring.iter().map(|p| p[0]).fold(-180.0, f64::max);
+                    east - west
+                })
+                .sum();
+            assert!((width - 360.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn collection() {
+        let collection = feature_collection(vec![iss_now(0, 1.0, 2.0).to_geojson()]);
+        assert_eq!(collection["type"], "FeatureCollection");
+        assert_eq!(collection["features"].as_array().unwrap().len(), 1);
+        assert!(iss_now(0, 1.0, 2.0)
+            .to_geojson_string()
+            .starts_with(r#"{"geometry""#));
+    }
+}
@@ -0,0 +1,252 @@
+//! Ground tracks of the ISS for drawing on a map.
+//!
+//! A `GroundTrack` is a series of points below the ISS, either
+//! computed with `orbit::Sgp4` for any number of orbits or collected
+//! from recorded `IssNow` samples. The points are split into segments
+//! where the track crosses the antimeridian, with both segments ending
+//! exactly at ±180°, so each segment can be drawn as a line without
+//! wrapping around the map.
+//!
+//! # Example
+//! ```
+//! use std::time::Duration;
+//! use open_notify_api::ground_track::GroundTrack;
+//! use open_notify_api::orbit::{Sgp4, Tle};
+//!
+//! let tle = Tle::parse(
+//!     "1 25544U 98067A   18084.88124977  .00001628  00000-0  31711-4 0  9991",
+//!     "2 25544  51.6416 359.2614 0001944 125.2585 357.1463 15.54190618105837",
+//! ).unwrap();
+//! let sgp4 = Sgp4::new(&tle).unwrap();
+//!
+//! let track = GroundTrack::from_sgp4(&sgp4, 1522012140, 2.0, Duration::from_secs(30)).unwrap();
+//! for segment in track.segments() {
+//!     println!("{} points", segment.len());
+//! }
+//! ```
+
+use std::time::Duration;
+
+use error::OpenNotificationError;
+use orbit::Sgp4;
+use IssNow;
+
+/// Point of a ground track.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackPoint {
+    timestamp: i64,
+    latitude: f64,
+    longitude: f64,
+}
+
+impl TrackPoint {
+    pub fn new(timestamp: i64, latitude: f64, longitude: f64) -> TrackPoint {
+        TrackPoint {
+            timestamp,
+            latitude,
+            longitude,
+        }
+    }
+
+    /// Unix timestamp the ISS is above the point.
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    /// Latitude in degrees
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    /// Longitude in degrees (-180 to 180)
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+}
+
+/// Ground track split into segments at the antimeridian.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GroundTrack {
+    segments: Vec<Vec<TrackPoint>>,
+}
+
+impl GroundTrack {
+    /// Track over `orbits` orbits from `start` on, with a point every
+    /// `step`.
+    pub fn from_sgp4(
+        sgp4: &Sgp4,
+        start: i64,
+        orbits: f64,
+        step: Duration,
+    ) -> Result<GroundTrack, OpenNotificationError> {
+        let step = step.as_secs() as i64;
+        if step < 1 {
+            return Err(OpenNotificationError::InvalidArgument(String::from(
+                "step must be at least one second",
+            )));
+        }
+        if !orbits.is_finite() || orbits <= 0.0 {
+            return Err(OpenNotificationError::InvalidArgument(format!(
+                "number of orbits {} must be positive",
+                orbits
+            )));
+        }
+
+        let end = start + (sgp4.period() * orbits).round() as i64;
+        let mut points = Vec::new();
+        let mut timestamp = start;
+        loop {
+            let position = sgp4.position(timestamp)?;
+            points.push(TrackPoint::new(
+                timestamp,
+                position.latitude(),
+                position.longitude(),
+            ));
+            if timestamp >= end {
+                break;
+            }
+            timestamp = (timestamp + step).min(end);
+        }
+
+        Ok(GroundTrack::from_points(&points, None))
+    }
+
+    /// Track through recorded samples in any order. Samples further
+    /// apart than `max_gap` are not connected.
+    pub fn from_history(samples: &[IssNow], max_gap: Duration) -> GroundTrack {
+        let mut points: Vec<TrackPoint> = samples
+            .iter()
+            .map(|s| TrackPoint::new(s.timestamp(), s.latitude_f64(), s.longitude_f64()))
+            .collect();
+        points.sort_by_key(|p| p.timestamp);
+        points.dedup_by_key(|p| p.timestamp);
+
+        GroundTrack::from_points(&points, Some(max_gap.as_secs() as i64))
+    }
+
+    /// Segments in chronological order.
+    pub fn segments(&self) -> &[Vec<TrackPoint>] {
+        &self.segments
+    }
+
+    /// All points in chronological order, including the points
+    /// inserted at the antimeridian.
+    pub fn points(&self) -> impl Iterator<Item = &TrackPoint> {
+        self.segments.iter().flat_map(|segment| segment.iter())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    fn from_points(points: &[TrackPoint], max_gap: Option<i64>) -> GroundTrack {
+        let mut segments = Vec::new();
+        let mut segment: Vec<TrackPoint> = Vec::new();
+
+        for point in points {
+            if let Some(&last) = segment.last() {
+                if max_gap.is_some_and(|gap| point.timestamp - last.timestamp > gap) {
+                    segments.push(segment);
+                    segment = Vec::new();
+                } else if (point.longitude - last.longitude).abs() > 180.0 {
+                    let (end, start) = antimeridian_crossing(&last, point);
+                    segment.push(end);
+                    segments.push(segment);
+                    segment = vec![start];
+                }
+            }
+            segment.push(*point);
+        }
+        if !segment.is_empty() {
+            segments.push(segment);
+        }
+
+        GroundTrack { segments }
+    }
+}
+
+/// Points at ±180° on both sides of the crossing between `from`
+/// and `to`.
+fn antimeridian_crossing(from: &TrackPoint, to: &TrackPoint) -> (TrackPoint, TrackPoint) {
+    let edge = if from.longitude > 0.0 { 180.0 } else { -180.0 };
+    let unwrapped = to.longitude + 2.0 * edge;
+    let f = (edge - from.longitude) / (unwrapped - from.longitude);
+
+    let latitude = from.latitude + f * (to.latitude - from.latitude);
+    let timestamp = from.timestamp + (f * (to.timestamp - from.timestamp) as f64).round() as i64;
+    (
+        TrackPoint::new(timestamp, latitude, edge),
+        TrackPoint::new(timestamp, latitude, -edge),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orbit::tests::iss_sgp4;
+    use tests::iss_now;
+
+    #[test]
+    fn track_from_sgp4() {
+        let sgp4 = iss_sgp4();
+        let start = 1522012140;
+        let track = GroundTrack::from_sgp4(&sgp4, start, 3.0, Duration::from_secs(60)).unwrap();
+
+        // About 92.6 minutes per orbit, crossing the antimeridian
+        // once per orbit.
+        assert!((sgp4.period() - 5556.0).abs() < 10.0);
+        assert!(track.segments().len() >= 3 && track.segments().len() <= 4);
+
+        let first = track.points().next().unwrap();
+        let last = track.points().last().unwrap();
+        assert_eq!(first.timestamp(), start);
+        assert_eq!(
+            last.timestamp(),
+            start + (sgp4.period() * 3.0).round() as i64
+        );
+
+        for segment in track.segments() {
+            for pair in segment.windows(2) {
+                assert!((pair[1].longitude() - pair[0].longitude()).abs() < 180.0);
+                assert!(pair[0].timestamp() <= pair[1].timestamp());
+            }
+        }
+        for pair in track.segments().windows(2) {
+            let end = pair[0].last().unwrap();
+            let start = pair[1].first().unwrap();
+            assert_eq!(end.longitude().abs(), 180.0);
+            assert_eq!(end.longitude(), -start.longitude());
+            assert_eq!(end.latitude(), start.latitude());
+        }
+    }
+
+    #[test]
+    fn track_from_history() {
+        let samples = [
+            iss_now(20, 11.0, -179.0),
+            iss_now(0, 10.0, 179.0),
+            iss_now(10, 10.0, 179.0),
+            iss_now(30, 12.0, -178.0),
+            iss_now(500, 20.0, -150.0),
+        ];
+        let track = GroundTrack::from_history(&samples, Duration::from_secs(60));
+
+        let segments = track.segments();
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].len(), 3);
+        let crossing = segments[0][2];
+        assert_eq!(crossing.longitude(), 180.0);
+        assert!((crossing.latitude() - 10.5).abs() < 1e-9);
+        assert_eq!(crossing.timestamp(), 15);
+        assert_eq!(segments[1][0].longitude(), -180.0);
+        assert_eq!(segments[1].len(), 3);
+        assert_eq!(segments[2].len(), 1);
+    }
+
+    #[test]
+    fn track_invalid_arguments() {
+        let sgp4 = iss_sgp4();
+        assert!(GroundTrack::from_sgp4(&sgp4, 0, 0.0, Duration::from_secs(60)).is_err());
+        assert!(GroundTrack::from_sgp4(&sgp4, 0, 1.0, Duration::from_millis(10)).is_err());
+    }
+}
@@ -0,0 +1,310 @@
+//! Append-only history of fetched positions and rosters.
+//!
+//! A `Recorder` keeps `IssNow` samples and `Astros` rosters in a
+//! directory as [JSON Lines](https://jsonlines.org/), one file per
+//! kind, each line being the serialized response. Files are only
+//! appended to, except when pruning. Lines that can't be read, e.g.
+//! left incomplete by a crash, are skipped, and the next record starts
+//! on a new line.
+//!
+//! `Astros` carries no timestamp, so rosters are recorded with the
+//! time they were fetched.
+//!
+//! # Example
+//! ```no_run
+//! use open_notify_api::history::Recorder;
+//!
+//! let recorder = Recorder::open("history").unwrap();
+//! let iss_now = open_notify_api::iss_now_from_json(
+//!     r#"{"message": "success", "timestamp": 1521971230,
+//!     "iss_position": {"latitude": -34.6445, "longitude": 73.5964}}"#,
+//! ).unwrap();
+//! let astros = open_notify_api::astro_from_json(r#"{"message": "success", "number": 2, "people": [
+//!     {"name": "Anton Shkaplerov", "craft": "ISS"},
+//!     {"name": "Scott Tingle", "craft": "ISS"}]}"#).unwrap();
+//! recorder.record_iss_now(&iss_now).unwrap();
+//! recorder.record_astros(&astros, iss_now.timestamp()).unwrap();
+//!
+//! let last_hour = recorder.positions(iss_now.timestamp() - 3600, iss_now.timestamp()).unwrap();
+//! let roster = recorder.roster_at(1522012140).unwrap();
+//!
+//! // Keep 30 days.
+//! recorder.prune(iss_now.timestamp() - 30 * 86400).unwrap();
+//! ```
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use {Astros, IssNow};
+
+const POSITIONS: &str = "iss_now.jsonl";
+const ROSTERS: &str = "astros.jsonl";
+
+#[derive(Deserialize, Serialize)]
+struct RecordedAstros {
+    recorded: i64,
+    astros: Astros,
+}
+
+#[derive(Serialize)]
+struct RecordingAstros<'a> {
+    recorded: i64,
+    astros: &'a Astros,
+}
+
+/// Records positions and rosters in a directory.
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    dir: PathBuf,
+}
+
+impl Recorder {
+    /// Uses `dir` for the history, creating it if needed.
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<Recorder> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Recorder {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Directory holding the history.
+    pub fn dir(&self) -> &Path {
+        self.dir.as_path()
+    }
+
+    /// Appends a position.
+    pub fn record_iss_now(&self, iss_now: &IssNow) -> io::Result<()> {
+        self.append(POSITIONS, iss_now)
+    }
+
+    /// Appends a roster fetched at the unix `timestamp`.
+    pub fn record_astros(&self, astros: &Astros, timestamp: i64) -> io::Result<()> {
+        self.append(
+            ROSTERS,
+            &RecordingAstros {
+                recorded: timestamp,
+                astros,
+            },
+        )
+    }
+
+    /// Positions with a timestamp from `from` to `to`, both included,
+    /// in chronological order.
+    pub fn positions(&self, from: i64, to: i64) -> io::Result<Vec<IssNow>> {
+        let mut positions: Vec<IssNow> = self
+            .read::<IssNow>(POSITIONS)?
+            .into_iter()
+            .filter(|p| p.timestamp() >= from && p.timestamp() <= to)
+            .collect();
+        positions.sort_by_key(|p| p.timestamp());
+        Ok(positions)
+    }
+
+    /// Latest roster recorded at or before the unix `timestamp`.
+    pub fn roster_at(&self, timestamp: i64) -> io::Result<Option<Astros>> {
+        Ok(self
+            .read::<RecordedAstros>(ROSTERS)?
+            .into_iter()
+            .filter(|r| r.recorded <= timestamp)
+            .max_by_key(|r| r.recorded)
+            .map(|r| r.astros))
+    }
+
+    /// Rosters recorded from `from` to `to`, both included, with the
+    /// time they were recorded, in chronological order.
+    pub fn rosters(&self, from: i64, to: i64) -> io::Result<Vec<(i64, Astros)>> {
+        let mut rosters: Vec<(i64, Astros)> = self
+            .read::<RecordedAstros>(ROSTERS)?
+            .into_iter()
+            .filter(|r| r.recorded >= from && r.recorded <= to)
+            .map(|r| (r.recorded, r.astros))
+            .collect();
+        rosters.sort_by_key(|&(recorded, _)| recorded);
+        Ok(rosters)
+    }
+
+    /// Removes records older than the unix timestamp `before` and
+    /// returns how many were removed. The latest roster before it is
+    /// kept, so `roster_at` still answers for any time from `before` on.
+    pub fn prune(&self, before: i64) -> io::Result<usize> {
+        let positions = self.read::<IssNow>(POSITIONS)?;
+        let count = positions.len();
+        let positions: Vec<IssNow> = positions
+            .into_iter()
+            .filter(|p| p.timestamp() >= before)
+            .collect();
+        let mut removed = count - positions.len();
+        self.rewrite(POSITIONS, &positions)?;
+
+        let rosters = self.read::<RecordedAstros>(ROSTERS)?;
+        let count = rosters.len();
+        let valid_since = rosters
+            .iter()
+            .map(|r| r.recorded)
+            .filter(|&recorded| recorded < before)
+            .max();
+        let rosters: Vec<RecordedAstros> = rosters
+            .into_iter()
+            .filter(|r| r.recorded >= before || Some(r.recorded) == valid_since)
+            .collect();
+        removed += count - rosters.len();
+        self.rewrite(ROSTERS, &rosters)?;
+
+        Ok(removed)
+    }
+
+    fn append<S: Serialize>(&self, file: &str, record: &S) -> io::Result<()> {
+        let mut line = serde_json::to_string(record).map_err(invalid_data)?;
+        line.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(self.dir.join(file))?;
+
+        // Terminate a line left incomplete, so the record isn't lost with it.
+        if file.seek(SeekFrom::End(0))? > 0 {
+            let mut last = [0];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                line.insert(0, '\n');
+            }
+        }
+        file.write_all(line.as_bytes())
+    }
+
+    fn read<D: DeserializeOwned>(&self, file: &str) -> io::Result<Vec<D>> {
+        let data = match fs::read_to_string(self.dir.join(file)) {
+            Ok(data) => data,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+        Ok(data
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    fn rewrite<S: Serialize>(&self, file: &str, records: &[S]) -> io::Result<()> {
+        let mut data = String::new();
+        for record in records {
+            data.push_str(&serde_json::to_string(record).map_err(invalid_data)?);
+            data.push('\n');
+        }
+        let path = self.dir.join(file);
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, &path)
+    }
+}
+
+fn invalid_data(error: serde_json::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tests::{astros, iss_now, temp_path};
+
+    fn names(astros: &Astros) -> Vec<&str> {
+        astros.people().iter().map(|p| p.name()).collect()
+    }
+
+    fn recorder(name: &str) -> Recorder {
+        let dir = temp_path(&format!("history-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        Recorder::open(&dir).unwrap()
+    }
+
+    #[test]
+    fn position_ranges() {
+        let recorder = recorder("positions");
+        assert!(recorder.positions(0, 100).unwrap().is_empty());
+
+        for &timestamp in &[30, 10, 20, 40] {
+            recorder
+                .record_iss_now(&iss_now(timestamp, 1.5, 2.5))
+                .unwrap();
+        }
+        // An incomplete line is skipped.
+        OpenOptions::new()
+            .append(true)
+            .open(recorder.dir().join(POSITIONS))
+            .unwrap()
+            .write_all(b"{\"message\": \"succ")
+            .unwrap();
+
+        let positions = recorder.positions(15, 30).unwrap();
+        let timestamps: Vec<i64> = positions.iter().map(|p| p.timestamp()).collect();
+        assert_eq!(timestamps, vec![20, 30]);
+        assert_eq!(positions[0].latitude(), 1.5);
+
+        // A record appended after it starts on a new line.
+        recorder.record_iss_now(&iss_now(50, 1.5, 2.5)).unwrap();
+        let positions = recorder.positions(0, 100).unwrap();
+        let timestamps: Vec<i64> = positions.iter().map(|p| p.timestamp()).collect();
+        assert_eq!(timestamps, vec![10, 20, 30, 40, 50]);
+
+        fs::remove_dir_all(recorder.dir()).unwrap();
+    }
+
+    #[test]
+    fn roster_as_of() {
+        let recorder = recorder("rosters");
+        assert!(recorder.roster_at(100).unwrap().is_none());
+
+        recorder
+            .record_astros(&astros(&[("A", "ISS"), ("B", "ISS")]), 100)
+            .unwrap();
+        recorder
+            .record_astros(&astros(&[("A", "ISS"), ("C", "ISS")]), 200)
+            .unwrap();
+
+        assert!(recorder.roster_at(99).unwrap().is_none());
+        assert_eq!(
+            names(&recorder.roster_at(150).unwrap().unwrap()),
+            vec!["A", "B"]
+        );
+        assert_eq!(
+            names(&recorder.roster_at(200).unwrap().unwrap()),
+            vec!["A", "C"]
+        );
+        assert_eq!(recorder.rosters(0, 150).unwrap().len(), 1);
+
+        fs::remove_dir_all(recorder.dir()).unwrap();
+    }
+
+    #[test]
+    fn retention() {
+        let recorder = recorder("prune");
+        for timestamp in 0..10 {
+            recorder
+                .record_iss_now(&iss_now(timestamp * 10, 1.5, 2.5))
+                .unwrap();
+        }
+        recorder.record_astros(&astros(&[("A", "ISS")]), 0).unwrap();
+        recorder
+            .record_astros(&astros(&[("B", "ISS")]), 30)
+            .unwrap();
+        recorder
+            .record_astros(&astros(&[("C", "ISS")]), 60)
+            .unwrap();
+
+        assert_eq!(recorder.prune(50).unwrap(), 5 + 1);
+        assert_eq!(recorder.positions(0, 100).unwrap().len(), 5);
+        assert_eq!(names(&recorder.roster_at(55).unwrap().unwrap()), vec!["B"]);
+        assert_eq!(recorder.rosters(0, 100).unwrap().len(), 2);
+
+        recorder.record_iss_now(&iss_now(100, 1.5, 2.5)).unwrap();
+        assert_eq!(recorder.positions(0, 100).unwrap().len(), 6);
+        assert_eq!(recorder.prune(50).unwrap(), 0);
+
+        fs::remove_dir_all(recorder.dir()).unwrap();
+    }
+}
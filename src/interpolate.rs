@@ -0,0 +1,364 @@
+//! Position estimates between and shortly after `IssNow` samples.
+//!
+//! Between two samples the ISS is assumed to move along the great
+//! circle connecting them at constant speed. Interpolating on the
+//! sphere instead of in latitude and longitude keeps estimates
+//! correct across the antimeridian and near the poles.
+//!
+//! After the last sample the motion along the last great circle is
+//! extrapolated for a limited time. The error of an estimate is
+//! derived from how far recent samples deviate from a great circle
+//! through their predecessors, so it needs at least three samples.
+//!
+//! # Example
+//! ```
+//! use open_notify_api::interpolate::Interpolator;
+//!
+//! let mut interpolator = Interpolator::new();
+//! for data in [
+//!     r#"{"message": "success", "timestamp": 100, "iss_position": {"latitude": 0, "longitude": 179.9}}"#,
+//!     r#"{"message": "success", "timestamp": 110, "iss_position": {"latitude": 0, "longitude": -179.9}}"#,
+//! ].iter() {
+//!     interpolator.push(&open_notify_api::iss_now_from_json(data).unwrap());
+//! }
+//!
+//! let estimate = interpolator.position_at(105.0).unwrap();
+//! assert!((estimate.longitude().abs() - 180.0).abs() < 1e-6);
+//! ```
+
+use std::f64::consts::PI;
+use std::time::Duration;
+
+use geo::distance;
+use IssNow;
+
+/// Default number of samples kept by an `Interpolator`.
+const DEFAULT_WINDOW: usize = 64;
+
+/// Estimated position of the ISS.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    timestamp: f64,
+    latitude: f64,
+    longitude: f64,
+    error: Option<f64>,
+    extrapolated: bool,
+}
+
+impl Estimate {
+    /// Unix timestamp of the estimate.
+    pub fn timestamp(&self) -> f64 {
+        self.timestamp
+    }
+
+    /// Latitude in degrees
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    /// Longitude in degrees (-180 to 180)
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    /// Estimated error in kilometers, `None` while fewer than three
+    /// samples are known.
+    pub fn error(&self) -> Option<f64> {
+        self.error
+    }
+
+    /// Whether the estimate lies after the last sample.
+    pub fn is_extrapolated(&self) -> bool {
+        self.extrapolated
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Fix {
+    timestamp: i64,
+    vector: [f64; 3],
+}
+
+/// Estimates the ISS position from a series of `IssNow` samples.
+#[derive(Clone, Debug)]
+pub struct Interpolator {
+    fixes: Vec<Fix>,
+    window: usize,
+    max_extrapolation: Duration,
+    deviation: Option<f64>,
+}
+
+impl Interpolator {
+    /// Interpolator keeping the last 64 samples and extrapolating up
+    /// to 60 seconds.
+    pub fn new() -> Interpolator {
+        Interpolator {
+            fixes: Vec::new(),
+            window: DEFAULT_WINDOW,
+            max_extrapolation: Duration::from_secs(60),
+            deviation: None,
+        }
+    }
+
+    /// Interpolator initialized with `samples` in any order.
+    pub fn from_samples(samples: &[IssNow]) -> Interpolator {
+        let mut interpolator = Interpolator::new();
+        for sample in samples {
+            interpolator.push(sample);
+        }
+        interpolator
+    }
+
+    /// Number of samples kept, older ones are dropped. At least two.
+    pub fn window(mut self, window: usize) -> Interpolator {
+        self.window = window.max(2);
+        self.trim();
+        self
+    }
+
+    /// How far past the last sample positions are extrapolated.
+    pub fn max_extrapolation(mut self, max_extrapolation: Duration) -> Interpolator {
+        self.max_extrapolation = max_extrapolation;
+        self
+    }
+
+    /// Adds a sample. Samples with a known timestamp are ignored.
+    pub fn push(&mut self, sample: &IssNow) {
+        let fix = Fix {
+            timestamp: sample.timestamp(),
+            vector: unit_vector(sample.latitude_f64(), sample.longitude_f64()),
+        };
+        match self
+            .fixes
+            .binary_search_by_key(&fix.timestamp, |f| f.timestamp)
+        {
+            Ok(_) => return,
+            Err(index) => self.fixes.insert(index, fix),
+        }
+        self.trim();
+        self.deviation = self.fit_deviation();
+    }
+
+    /// Number of samples known.
+    pub fn len(&self) -> usize {
+        self.fixes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fixes.is_empty()
+    }
+
+    /// Estimated position at the fractional unix `timestamp`.
+    ///
+    /// `None` before the first sample, with less than two samples or
+    /// further than the maximal extrapolation past the last sample.
+    pub fn position_at(&self, timestamp: f64) -> Option<Estimate> {
+        if self.fixes.len() < 2 || timestamp < self.fixes[0].timestamp as f64 {
+            return None;
+        }
+
+        let index = match self
+            .fixes
+            .iter()
+            .position(|f| f.timestamp as f64 >= timestamp)
+        {
+            Some(0) => 1,
+            Some(index) => index,
+            None => self.fixes.len() - 1,
+        };
+        let (from, to) = (self.fixes[index - 1], self.fixes[index]);
+        let (t1, t2) = (from.timestamp as f64, to.timestamp as f64);
+
+        let extrapolated = timestamp > t2;
+        if extrapolated && timestamp - t2 > self.max_extrapolation.as_secs_f64() {
+            return None;
+        }
+
+        let (latitude, longitude) = coordinates(&slerp(
+            &from.vector,
+            &to.vector,
+            (timestamp - t1) / (t2 - t1),
+        ));
+        let error = self.deviation.map(|k| {
+            if extrapolated {
+                k * (timestamp - t2) * (timestamp - t1)
+            } else {
+                k * (timestamp - t1) * (t2 - timestamp)
+            }
+        });
+
+        Some(Estimate {
+            timestamp,
+            latitude,
+            longitude,
+            error,
+            extrapolated,
+        })
+    }
+
+    fn trim(&mut self) {
+        if self.fixes.len() > self.window {
+            let excess = self.fixes.len() - self.window;
+            self.fixes.drain(..excess);
+        }
+    }
+
+    /// Largest deviation from great circle motion in km/s², found by
+    /// predicting each sample from its two predecessors.
+    fn fit_deviation(&self) -> Option<f64> {
+        self.fixes
+            .windows(3)
+            .map(|w| {
+                let (t1, t2, t3) = (
+                    w[0].timestamp as f64,
+                    w[1].timestamp as f64,
+                    w[2].timestamp as f64,
+                );
+                let predicted =
+                    coordinates(&slerp(&w[0].vector, &w[1].vector, (t3 - t1) / (t2 - t1)));
+                let actual = coordinates(&w[2].vector);
+                let miss = distance(predicted.0, predicted.1, actual.0, actual.1);
+                miss / ((t3 - t2) * (t3 - t1))
+            })
+            .fold(None, |max: Option<f64>, k| {
+                Some(max.map_or(k, |m| m.max(k)))
+            })
+    }
+}
+
+impl Default for Interpolator {
+    fn default() -> Interpolator {
+        Interpolator::new()
+    }
+}
+
+fn unit_vector(latitude: f64, longitude: f64) -> [f64; 3] {
+    let (sin_lat, cos_lat) = latitude.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = longitude.to_radians().sin_cos();
+    [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat]
+}
+
+/// Latitude and longitude in degrees of a unit vector.
+fn coordinates(vector: &[f64; 3]) -> (f64, f64) {
+    (
+        vector[2].clamp(-1.0, 1.0).asin().to_degrees(),
+        vector[1].atan2(vector[0]).to_degrees(),
+    )
+}
+
+/// Point at fraction `f` of the great circle arc from `a` to `b`,
+/// continuing along the circle for `f` outside 0 to 1. Antipodal
+/// points have no unique arc, so the nearer of them is returned.
+fn slerp(a: &[f64; 3], b: &[f64; 3], f: f64) -> [f64; 3] {
+    let dot = (a[0] * b[0] + a[1] * b[1] + a[2] * b[2]).clamp(-1.0, 1.0);
+    let angle = dot.acos();
+    if PI - angle < 1e-6 {
+        return if f < 0.5 { *a } else { *b };
+    }
+    let (wa, wb) = if angle < 1e-12 {
+        (1.0 - f, f)
+    } else {
+        (
+            ((1.0 - f) * angle).sin() / angle.sin(),
+            (f * angle).sin() / angle.sin(),
+        )
+    };
+
+    let v = [
+        wa * a[0] + wb * b[0],
+        wa * a[1] + wb * b[1],
+        wa * a[2] + wb * b[2],
+    ];
+    let norm = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    [v[0] / norm, v[1] / norm, v[2] / norm]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tests::iss_now;
+
+    #[test]
+    fn interpolate_across_antimeridian() {
+        let interpolator =
+            Interpolator::from_samples(&[iss_now(110, 0.0, -179.0), iss_now(100, 0.0, 179.0)]);
+
+        let estimate = interpolator.position_at(105.0).unwrap();
+        assert!(estimate.latitude().abs() < 1e-6);
+        assert!((estimate.longitude().abs() - 180.0).abs() < 1e-4);
+        assert!(!estimate.is_extrapolated());
+        assert_eq!(estimate.error(), None);
+
+        let estimate = interpolator.position_at(107.5).unwrap();
+        assert!((estimate.longitude() + 179.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn interpolate_antipodal_samples() {
+        let interpolator =
+            Interpolator::from_samples(&[iss_now(0, 0.0, 0.0), iss_now(10, 0.0, 180.0)]);
+
+        let estimate = interpolator.position_at(4.0).unwrap();
+        assert_eq!((estimate.latitude(), estimate.longitude()), (0.0, 0.0));
+        let estimate = interpolator.position_at(5.0).unwrap();
+        assert!(estimate.latitude().is_finite() && estimate.longitude().is_finite());
+        let estimate = interpolator.position_at(6.0).unwrap();
+        assert!(estimate.latitude().is_finite());
+        assert!((estimate.longitude().abs() - 180.0).abs() < 1e-9);
+        assert!(interpolator
+            .position_at(15.0)
+            .unwrap()
+            .latitude()
+            .is_finite());
+    }
+
+    #[test]
+    fn interpolate_over_pole() {
+        let interpolator =
+            Interpolator::from_samples(&[iss_now(0, 89.0, 10.0), iss_now(10, 89.0, -170.0)]);
+
+        let estimate = interpolator.position_at(5.0).unwrap();
+        assert!((estimate.latitude() - 90.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn extrapolate_great_circle() {
+        let mut interpolator = Interpolator::new().max_extrapolation(Duration::from_secs(30));
+        interpolator.push(&iss_now(0, 0.0, 0.0));
+        interpolator.push(&iss_now(10, 0.0, 1.0));
+        interpolator.push(&iss_now(20, 0.0, 2.0));
+        interpolator.push(&iss_now(20, 5.0, 5.0));
+        assert_eq!(interpolator.len(), 3);
+
+        let estimate = interpolator.position_at(35.0).unwrap();
+        assert!(estimate.is_extrapolated());
+        assert!((estimate.longitude() - 3.5).abs() < 1e-4);
+        assert!(estimate.error().unwrap() < 1e-3);
+
+        assert!(interpolator.position_at(51.0).is_none());
+        assert!(interpolator.position_at(-1.0).is_none());
+    }
+
+    #[test]
+    fn error_covers_curved_track() {
+        // Constant latitude is not a great circle.
+        let samples: Vec<IssNow> = (0..4).map(|i| iss_now(i * 10, 45.0, i as f32)).collect();
+        let interpolator = Interpolator::from_samples(&samples);
+
+        let truth = iss_now(50, 45.0, 5.0);
+        let estimate = interpolator.position_at(50.0).unwrap();
+        let miss = distance(
+            estimate.latitude(),
+            estimate.longitude(),
+            truth.latitude_f64(),
+            truth.longitude_f64(),
+        );
+        let error = estimate.error().unwrap();
+        assert!(miss > 0.1);
+        assert!(miss <= error * 1.05);
+
+        let estimate = interpolator.position_at(15.0).unwrap();
+        assert!(estimate.error().unwrap() < error);
+        assert!((estimate.latitude() - 45.0).abs() < 0.01);
+    }
+}
@@ -10,8 +10,68 @@
 //!
 //! * Request ISS pass times given a location
 //!
+//! The free functions use the public api with default settings and
+//! share one client, created on first use.
+//! Use `OpenNotifyClient` to configure the base url, timeout or
+//! user agent, or to plug in a different HTTP stack by implementing
+//! `transport::Transport`. The default `reqwest` transport and the
+//! free functions are only available with the `reqwest` feature.
+//!
+//! The `async` feature adds `AsyncOpenNotifyClient`, a non-blocking
+//! client returning the same types.
+//!
+//! The `chrono` feature adds typed accessors for timestamps and
+//! durations, e.g. `IssNow::datetime` and `IssPassTime::rise_datetime`.
+//!
+//! `cache::CachedClient` serves repeated requests from an in-memory
+//! or on-disk cache with a time to live per endpoint.
+//!
+//! Failed requests can be retried with exponential backoff and a
+//! circuit breaker can stop requests while open-notify is down, see
+//! `retry`.
+//!
+//! `Astros::crafts` groups people by their craft, with craft names
+//! normalized by `craft::Craft`.
+//!
+//! The `metadata` feature adds bundled details about people in space
+//! and their crafts, like agency and launch date, see `metadata`.
+//!
+//! The `mock` feature adds `mock::MockServer`, a local stand-in for
+//! the open-notify api to test clients without network access.
+//!
+//! `Astros::diff` reports arrivals, departures and transfers between
+//! crafts, `roster::RosterTracker` remembers how long people have
+//! been in space.
+//!
+//! `history::Recorder` keeps fetched positions and rosters in JSON
+//! Lines files, with time range queries and pruning.
+//!
+//! `track::Tracker` polls the ISS position at a fixed interval, as an
+//! `Iterator` or, with the `async` feature, as a `Stream`.
+//!
+//! `geo` adds great circle distances, bearings and ground speed
+//! between `IssNow` samples and the distance to an observer.
+//!
+//! `geojson` exports positions, ground tracks and visibility
+//! footprints as GeoJSON features for web maps and GIS tools.
+//!
+//! The `export` feature adds KML and GPX output for Google Earth and
+//! GPS tools and iCalendar files of passes, see `export`.
+//!
+//! `ground_track::GroundTrack` builds map ready ground tracks from the
+//! propagator or from recorded samples.
+//!
+//! `interpolate::Interpolator` estimates positions between and shortly
+//! after samples, e.g. to animate a map between polls.
+//!
+//! Positions can also be computed offline from orbital elements with
+//! the SGP4 propagator in `orbit`, and passes over an observer with
+//! `predict::PassPredictor`, which also tells which passes are
+//! visible to the naked eye.
+//!
 //! # Example
-//! ```
+#![cfg_attr(feature = "reqwest", doc = "```")]
+#![cfg_attr(not(feature = "reqwest"), doc = "```ignore")]
 //! match open_notify_api::astros() {
 //!     Ok(astros) => {
 //!         println!("People in space {}", astros.people().len());
@@ -25,6 +85,11 @@
 //! }
 //! ```
 
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "async")]
+extern crate futures;
+#[cfg(feature = "reqwest")]
 extern crate reqwest;
 extern crate serde;
 extern crate serde_json;
@@ -32,11 +97,47 @@ extern crate serde_json;
 #[macro_use]
 extern crate serde_derive;
 
+#[cfg(feature = "async")]
+pub mod async_client;
+pub mod cache;
+pub mod client;
+pub mod craft;
 pub mod error;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod geo;
+pub mod geojson;
+pub mod ground_track;
+pub mod history;
+pub mod interpolate;
+#[cfg(feature = "metadata")]
+pub mod metadata;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod orbit;
+pub mod predict;
+pub mod request;
+pub mod retry;
+pub mod roster;
+pub mod sun;
+#[cfg(feature = "chrono")]
+mod time;
+pub mod track;
+pub mod transport;
+
+#[cfg(feature = "async")]
+pub use async_client::AsyncOpenNotifyClient;
+pub use client::{OpenNotifyClient, OpenNotifyClientBuilder};
+pub use request::IssPassRequest;
+
+#[cfg(feature = "reqwest")]
+use std::sync::OnceLock;
+#[cfg(feature = "reqwest")]
+use transport::ReqwestTransport;
 
 /// People are contained in a separate type `Person`
 /// to add the information in which craft they are in.
-#[derive(Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Person {
     name: String,
     craft: String,
@@ -114,45 +215,85 @@ impl IssNow {
     pub fn longitude(&self) -> f32 {
         self.iss_position.longitude
     }
+
+    /// Latitude of the ISS as written in the response, without the
+    /// noise of widening the `f32`, e.g. `-34.6445` instead of
+    /// `-34.64450073242188`.
+    pub fn latitude_f64(&self) -> f64 {
+        coordinate(self.iss_position.latitude)
+    }
+
+    /// Longitude of the ISS as written in the response, see
+    /// `latitude_f64`.
+    pub fn longitude_f64(&self) -> f64 {
+        coordinate(self.iss_position.longitude)
+    }
+}
+
+/// Widens a coordinate of a response to `f64` as written in the
+/// response, without the noise of the binary `f32` value, so
+/// `-34.6445` stays `-34.6445` instead of `-34.64450073242188`.
+pub(crate) fn coordinate(value: f32) -> f64 {
+    value
+        .to_string()
+        .parse()
+        .unwrap_or_else(|_| f64::from(value))
+}
+
+/// Client with default settings shared by the free functions, created
+/// on first use. Creating it is retried until it succeeds.
+#[cfg(feature = "reqwest")]
+fn default_client(
+) -> Result<&'static OpenNotifyClient<ReqwestTransport>, error::OpenNotificationError> {
+    static CLIENT: OnceLock<OpenNotifyClient<ReqwestTransport>> = OnceLock::new();
+    if let Some(client) = CLIENT.get() {
+        return Ok(client);
+    }
+    let client = OpenNotifyClient::new()?;
+    Ok(CLIENT.get_or_init(|| client))
 }
 
 /// Fetch astronouts currently in space.
+#[cfg(feature = "reqwest")]
 pub fn astros() -> Result<Astros, error::OpenNotificationError> {
-    astro_from_json(&reqwest::get("http://api.open-notify.org/astros.json")?.text()?)
+    default_client()?.astros()
 }
 
-fn astro_from_json(data: &str) -> Result<Astros, error::OpenNotificationError> {
+/// Parses and validates the body of an `astros.json` response.
+pub fn astro_from_json(data: &str) -> Result<Astros, error::OpenNotificationError> {
     let astros: Astros = serde_json::from_str(data)?;
 
+    if astros.message != "success" {
+        return Err(error::OpenNotificationError::Upstream(astros.reason));
+    }
+
     if astros.number as usize != astros.people.len() {
         return Err(error::OpenNotificationError::Data(String::from(
             "attribute 'number' does not match length of people field",
         )));
     }
 
-    if astros.message != "success" {
-        return Err(error::OpenNotificationError::Data(astros.reason));
-    }
-
     Ok(astros)
 }
 
 /// Fetch current ISS position.
+#[cfg(feature = "reqwest")]
 pub fn iss_now() -> Result<IssNow, error::OpenNotificationError> {
-    iss_now_from_json(&reqwest::get("http://api.open-notify.org/iss-now.json")?.text()?)
+    default_client()?.iss_now()
 }
 
-fn iss_now_from_json(data: &str) -> Result<IssNow, error::OpenNotificationError> {
+/// Parses and validates the body of an `iss-now.json` response.
+pub fn iss_now_from_json(data: &str) -> Result<IssNow, error::OpenNotificationError> {
     let iss_now: IssNow = serde_json::from_str(data)?;
 
     if iss_now.message != "success" {
-        return Err(error::OpenNotificationError::Data(iss_now.reason));
+        return Err(error::OpenNotificationError::Upstream(iss_now.reason));
     }
 
     Ok(iss_now)
 }
 
-#[derive(Default, Deserialize, Serialize)]
+#[derive(Deserialize, Serialize)]
 struct IssPassTimesRequest {
     latitude: f32,
     longitude: f32,
@@ -175,6 +316,12 @@ impl IssPassTime {
     pub fn duration(&self) -> i64 {
         self.duration
     }
+
+    /// Returns the unix timestamp when the ISS sets
+    /// below the horizon again.
+    pub fn set_time(&self) -> i64 {
+        self.risetime.saturating_add(self.duration)
+    }
 }
 
 /// Structure containing the location of the ISS.
@@ -184,7 +331,7 @@ pub struct IssPassTimes {
     #[serde(default)]
     reason: String,
     #[serde(default)]
-    request: IssPassTimesRequest,
+    request: Option<IssPassTimesRequest>,
     #[serde(default)]
     response: Vec<IssPassTime>,
 }
@@ -193,6 +340,44 @@ impl IssPassTimes {
     pub fn passes(&self) -> &[IssPassTime] {
         &self.response
     }
+
+    /// Whether the server echoed the request. Without it the
+    /// accessors for the request return zero.
+    pub fn has_request(&self) -> bool {
+        self.request.is_some()
+    }
+
+    /// Latitude as echoed by the server
+    pub fn latitude(&self) -> f32 {
+        self.request
+            .as_ref()
+            .map_or(0.0, |request| request.latitude)
+    }
+
+    /// Longitude as echoed by the server
+    pub fn longitude(&self) -> f32 {
+        self.request
+            .as_ref()
+            .map_or(0.0, |request| request.longitude)
+    }
+
+    /// Altitude as echoed by the server
+    pub fn altitude(&self) -> f32 {
+        self.request
+            .as_ref()
+            .map_or(0.0, |request| request.altitude)
+    }
+
+    /// Number of requested passes as echoed by the server
+    pub fn requested_passes(&self) -> u32 {
+        self.request.as_ref().map_or(0, |request| request.passes)
+    }
+
+    /// Unix timestamp the search for passes started at,
+    /// as echoed by the server.
+    pub fn request_timestamp(&self) -> i64 {
+        self.request.as_ref().map_or(0, |request| request.datetime)
+    }
 }
 
 /// Request ISS pass times over a specified location
@@ -203,6 +388,11 @@ impl IssPassTimes {
 /// * `alt` 0 to 10000 in meters
 /// * `n` 1 to 100; How many passes shall be included in the result.
 ///
+/// Parameters out of range are rejected with
+/// `OpenNotificationError::InvalidArgument` without sending a request.
+/// Use `IssPassRequest` and `OpenNotifyClient::iss_pass_times_for` to
+/// also set the start time of the search.
+///
 /// # Example
 /// ```rust
 /// use open_notify_api as ona;
@@ -210,33 +400,120 @@ impl IssPassTimes {
 ///     assert_eq!(reply.passes().len(), 5);
 /// }
 /// ```
+#[cfg(feature = "reqwest")]
 pub fn iss_pass_times(
     lat: f32,
     lon: f32,
     alt: f32,
     n: u32,
 ) -> Result<IssPassTimes, error::OpenNotificationError> {
-    iss_pass_times_from_json(&reqwest::get(
-        format!(
-            "http://api.open-notify.org/iss-pass.json?lat={}&lon={}&alt={}&n={}",
-            lat, lon, alt, n,
-        ).as_str(),
-    )?.text()?)
+    default_client()?.iss_pass_times(lat, lon, alt, n)
 }
 
-fn iss_pass_times_from_json(data: &str) -> Result<IssPassTimes, error::OpenNotificationError> {
+/// Parses and validates the body of an `iss-pass.json` response.
+pub fn iss_pass_times_from_json(data: &str) -> Result<IssPassTimes, error::OpenNotificationError> {
     let iss_pass_times: IssPassTimes = serde_json::from_str(data)?;
 
     if iss_pass_times.message != "success" {
-        return Err(error::OpenNotificationError::Data(iss_pass_times.reason));
+        return Err(error::OpenNotificationError::Upstream(
+            iss_pass_times.reason,
+        ));
+    }
+
+    Ok(iss_pass_times)
+}
+
+/// Parses and validates the body of an `iss-pass.json` response
+/// and checks it against the `request` it answers.
+///
+/// In addition to `iss_pass_times_from_json` the echoed request has
+/// to match `request` and the response has to contain as many passes
+/// as requested.
+pub fn iss_pass_times_from_json_checked(
+    data: &str,
+    request: &IssPassRequest,
+) -> Result<IssPassTimes, error::OpenNotificationError> {
+    let iss_pass_times = iss_pass_times_from_json(data)?;
+
+    let echoed = [
+        ("latitude", iss_pass_times.latitude(), request.latitude()),
+        ("longitude", iss_pass_times.longitude(), request.longitude()),
+        ("altitude", iss_pass_times.altitude(), request.altitude()),
+    ];
+    for &(name, echoed, requested) in echoed.iter() {
+        if (echoed - requested).abs() > 1e-3 {
+            return Err(error::OpenNotificationError::Data(format!(
+                "echoed {} {} does not match requested {}",
+                name, echoed, requested
+            )));
+        }
+    }
+
+    if iss_pass_times.requested_passes() != request.passes() {
+        return Err(error::OpenNotificationError::Data(format!(
+            "echoed passes {} does not match requested {}",
+            iss_pass_times.requested_passes(),
+            request.passes()
+        )));
+    }
+
+    if let Some(datetime) = request.datetime() {
+        if iss_pass_times.request_timestamp() != datetime {
+            return Err(error::OpenNotificationError::Data(format!(
+                "echoed datetime {} does not match requested {}",
+                iss_pass_times.request_timestamp(),
+                datetime
+            )));
+        }
+    }
+
+    if iss_pass_times.passes().len() != request.passes() as usize {
+        return Err(error::OpenNotificationError::Data(String::from(
+            "length of response field does not match requested passes",
+        )));
     }
 
     Ok(iss_pass_times)
 }
 
 #[cfg(test)]
-mod tests {
+#[allow(
+    clippy::assertions_on_constants,
+    clippy::needless_borrow,
+    clippy::useless_vec
+)]
+pub(crate) mod tests {
     use super::*;
+    use std::env;
+    use std::path::PathBuf;
+    use std::process;
+
+    pub fn iss_now(timestamp: i64, latitude: f32, longitude: f32) -> IssNow {
+        iss_now_from_json(&format!(
+            r#"{{"message": "success", "timestamp": {},
+            "iss_position": {{"latitude": {}, "longitude": {}}}}}"#,
+            timestamp, latitude, longitude
+        ))
+        .unwrap()
+    }
+
+    pub fn astros(people: &[(&str, &str)]) -> Astros {
+        let people: Vec<String> = people
+            .iter()
+            .map(|(name, craft)| format!(r#"{{"name": "{}", "craft": "{}"}}"#, name, craft))
+            .collect();
+        astro_from_json(&format!(
+            r#"{{"message": "success", "number": {}, "people": [{}]}}"#,
+            people.len(),
+            people.join(", ")
+        ))
+        .unwrap()
+    }
+
+    /// Path in the temporary directory unique to this test process.
+    pub fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("open-notify-{}-{}", process::id(), name))
+    }
 
     #[test]
     fn astro_parse_successful_data() {
@@ -320,9 +597,9 @@ mod tests {
             "reason": "something went wrong"
             }"#;
 
-        use error::OpenNotificationError::Data;
+        use error::OpenNotificationError::Upstream;
         match astro_from_json(input_data) {
-            Err(Data(msg)) => assert_eq!(msg, "something went wrong"),
+            Err(Upstream(msg)) => assert_eq!(msg, "something went wrong"),
             Err(_) => assert!(false),
             Ok(_) => assert!(false),
         }
@@ -350,11 +627,112 @@ mod tests {
             "reason": "something went wrong"
             }"#;
 
-        use error::OpenNotificationError::Data;
+        use error::OpenNotificationError::Upstream;
         match iss_now_from_json(input_data) {
-            Err(Data(msg)) => assert_eq!(msg, "something went wrong"),
+            Err(Upstream(msg)) => assert_eq!(msg, "something went wrong"),
             Err(_) => assert!(false),
             Ok(_) => assert!(false),
         }
     }
+
+    const ISS_PASS_TIMES_DATA: &str = r#"{
+        "message": "success",
+        "request": {"latitude": 52.5, "longitude": 13.4, "altitude": 10,
+                    "passes": 2, "datetime": 1521971230},
+        "response": [
+        {"risetime": 1521985612, "duration": 496},
+        {"risetime": 1521991415, "duration": 646}]
+        }"#;
+
+    #[test]
+    fn iss_pass_times_parse_echoed_request() {
+        if let Ok(pass_times) = iss_pass_times_from_json(ISS_PASS_TIMES_DATA) {
+            assert_eq!(pass_times.latitude(), 52.5);
+            assert_eq!(pass_times.longitude(), 13.4);
+            assert_eq!(pass_times.altitude(), 10.0);
+            assert_eq!(pass_times.requested_passes(), 2);
+            assert_eq!(pass_times.request_timestamp(), 1521971230);
+        } else {
+            panic!();
+        }
+    }
+
+    #[test]
+    fn iss_pass_times_parse_checked_data() {
+        let request = IssPassRequest::builder(52.5, 13.4)
+            .altitude(10.0)
+            .passes(2)
+            .datetime(1521971230)
+            .build()
+            .unwrap();
+
+        assert!(iss_pass_times_from_json_checked(ISS_PASS_TIMES_DATA, &request).is_ok());
+    }
+
+    #[test]
+    fn iss_pass_times_parse_mismatching_data() {
+        let requests = [
+            IssPassRequest::builder(52.0, 13.4).altitude(10.0).passes(2),
+            IssPassRequest::builder(52.5, 13.4).altitude(20.0).passes(2),
+            IssPassRequest::builder(52.5, 13.4).altitude(10.0).passes(3),
+            IssPassRequest::builder(52.5, 13.4)
+                .altitude(10.0)
+                .passes(2)
+                .datetime(1521971231),
+        ];
+
+        use error::OpenNotificationError::Data;
+        for builder in requests.iter().cloned() {
+            let request = builder.build().unwrap();
+            match iss_pass_times_from_json_checked(ISS_PASS_TIMES_DATA, &request) {
+                Err(Data(_)) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    #[test]
+    fn iss_pass_times_parse_missing_passes() {
+        let input_data = r#"{
+            "message": "success",
+            "request": {"latitude": 52.5, "longitude": 13.4, "altitude": 10,
+                        "passes": 2, "datetime": 1521971230},
+            "response": [{"risetime": 1521985612, "duration": 496}]
+            }"#;
+        let request = IssPassRequest::builder(52.5, 13.4)
+            .altitude(10.0)
+            .passes(2)
+            .build()
+            .unwrap();
+
+        use error::OpenNotificationError::Data;
+        match iss_pass_times_from_json_checked(input_data, &request) {
+            Err(Data(_)) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn coordinate_as_written() {
+        assert_eq!(coordinate(-34.6445), -34.6445);
+        assert_eq!(coordinate(73.5964), 73.5964);
+        assert_eq!(coordinate(0.0), 0.0);
+
+        let iss_now = iss_now(1521971230, -34.6445, 73.5964);
+        assert_eq!(iss_now.latitude_f64(), -34.6445);
+        assert_eq!(iss_now.longitude_f64(), 73.5964);
+    }
+
+    #[test]
+    fn iss_pass_time_set_time_saturates() {
+        let pass_times = iss_pass_times_from_json(&format!(
+            r#"{{"message": "success",
+            "request": {{"latitude": 52.5, "longitude": 13.4, "altitude": 10,
+                        "passes": 1, "datetime": 1521971230}},
+            "response": [{{"risetime": {}, "duration": 496}}]}}"#,
+            i64::MAX - 10
+        ))
+        .unwrap();
+        assert_eq!(pass_times.passes()[0].set_time(), i64::MAX);
+    }
 }
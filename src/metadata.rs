@@ -0,0 +1,327 @@
+//! Offline details about people in space and their crafts, with the
+//! `metadata` feature.
+//!
+//! open-notify only reports names and crafts. `Metadata::bundled`
+//! adds agency, nationality, role and launch from `data/metadata.json`
+//! shipped with the crate. The bundled data only covers crews known
+//! at release time, so newer data can be loaded from a JSON file of
+//! the same format, overriding the bundled entries field by field.
+//!
+//! People are looked up by name and crafts by normalized name, see
+//! `craft::Craft`. Lookups never fail: unknown people get empty
+//! details, completed with the launch of their craft if it is known.
+//!
+//! # Example
+//! ```
+//! use open_notify_api::metadata::Metadata;
+//! use open_notify_api::Person;
+//!
+//! let metadata = Metadata::bundled();
+//! let info = metadata.person(&Person::new("Norishige Kanai", "ISS"));
+//! assert_eq!(info.agency(), Some("JAXA"));
+//!
+//! let unknown = metadata.person(&Person::new("Jane Doe", "Soyuz MS-08"));
+//! assert_eq!(unknown.agency(), None);
+//! assert_eq!(unknown.launch_date(), Some("2018-03-21"));
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use craft::Craft;
+use error::OpenNotificationError;
+use Person;
+
+const BUNDLED: &str = include_str!("../data/metadata.json");
+
+/// Details about a person in space.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct PersonInfo {
+    #[serde(default)]
+    agency: Option<String>,
+    #[serde(default)]
+    nationality: Option<String>,
+    #[serde(default)]
+    role: Option<String>,
+    #[serde(default)]
+    launch_vehicle: Option<String>,
+    #[serde(default)]
+    launch_date: Option<String>,
+}
+
+impl PersonInfo {
+    /// Space agency, e.g. `NASA`
+    pub fn agency(&self) -> Option<&str> {
+        self.agency.as_deref()
+    }
+
+    pub fn nationality(&self) -> Option<&str> {
+        self.nationality.as_deref()
+    }
+
+    /// Role on board, e.g. `Commander`
+    pub fn role(&self) -> Option<&str> {
+        self.role.as_deref()
+    }
+
+    /// Rocket of the launch to space
+    pub fn launch_vehicle(&self) -> Option<&str> {
+        self.launch_vehicle.as_deref()
+    }
+
+    /// Launch date as `YYYY-MM-DD`
+    pub fn launch_date(&self) -> Option<&str> {
+        self.launch_date.as_deref()
+    }
+
+    fn merge(&mut self, other: PersonInfo) {
+        merge(&mut self.agency, other.agency);
+        merge(&mut self.nationality, other.nationality);
+        merge(&mut self.role, other.role);
+        merge(&mut self.launch_vehicle, other.launch_vehicle);
+        merge(&mut self.launch_date, other.launch_date);
+    }
+}
+
+/// Details about a spacecraft or space station.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct CraftInfo {
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    operator: Option<String>,
+    #[serde(default)]
+    launch_vehicle: Option<String>,
+    #[serde(default)]
+    launch_date: Option<String>,
+}
+
+impl CraftInfo {
+    /// Kind of craft, e.g. `Space station`
+    pub fn kind(&self) -> Option<&str> {
+        self.kind.as_deref()
+    }
+
+    /// Agencies operating the craft
+    pub fn operator(&self) -> Option<&str> {
+        self.operator.as_deref()
+    }
+
+    /// Rocket of the launch of the craft or its first module
+    pub fn launch_vehicle(&self) -> Option<&str> {
+        self.launch_vehicle.as_deref()
+    }
+
+    /// Launch date as `YYYY-MM-DD`
+    pub fn launch_date(&self) -> Option<&str> {
+        self.launch_date.as_deref()
+    }
+
+    fn merge(&mut self, other: CraftInfo) {
+        merge(&mut self.kind, other.kind);
+        merge(&mut self.operator, other.operator);
+        merge(&mut self.launch_vehicle, other.launch_vehicle);
+        merge(&mut self.launch_date, other.launch_date);
+    }
+}
+
+#[derive(Deserialize)]
+struct MetadataFile {
+    #[serde(default)]
+    people: HashMap<String, PersonInfo>,
+    #[serde(default)]
+    crafts: HashMap<String, CraftInfo>,
+}
+
+/// Details about people and crafts.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    people: HashMap<String, PersonInfo>,
+    crafts: HashMap<Craft, CraftInfo>,
+}
+
+impl Metadata {
+    /// Data shipped with the crate.
+    pub fn bundled() -> Metadata {
+        Metadata::from_json(BUNDLED).expect("bundled metadata is valid")
+    }
+
+    /// Bundled data overridden by the file at `path`.
+    pub fn with_overrides<P: AsRef<Path>>(path: P) -> io::Result<Metadata> {
+        let overrides = Metadata::load(path)?;
+        Ok(Metadata::bundled().merge(overrides))
+    }
+
+    /// Data from the JSON file at `path`, in the format of
+    /// `data/metadata.json`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Metadata> {
+        let data = fs::read_to_string(path)?;
+        Metadata::from_json(&data)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Data from JSON in the format of `data/metadata.json`.
+    pub fn from_json(data: &str) -> Result<Metadata, OpenNotificationError> {
+        let file: MetadataFile =
+            serde_json::from_str(data).map_err(OpenNotificationError::Parsing)?;
+        Ok(Metadata {
+            people: file
+                .people
+                .into_iter()
+                .map(|(name, info)| (person_key(&name), info))
+                .collect(),
+            crafts: file
+                .crafts
+                .into_iter()
+                .map(|(name, info)| (Craft::new(&name), info))
+                .collect(),
+        })
+    }
+
+    /// Adds the entries of `overrides`. Fields set in both come from
+    /// `overrides`.
+    pub fn merge(mut self, overrides: Metadata) -> Metadata {
+        for (name, info) in overrides.people {
+            self.people.entry(name).or_default().merge(info);
+        }
+        for (craft, info) in overrides.crafts {
+            self.crafts.entry(craft).or_default().merge(info);
+        }
+        self
+    }
+
+    /// Details about `person`. Launch vehicle and date missing for the
+    /// person are each taken from their craft.
+    pub fn person(&self, person: &Person) -> PersonInfo {
+        let mut info = self.person_info(person.name()).cloned().unwrap_or_default();
+        if let Some(craft) = self.craft(person.craft()) {
+            if info.launch_vehicle.is_none() {
+                info.launch_vehicle = craft.launch_vehicle.clone();
+            }
+            if info.launch_date.is_none() {
+                info.launch_date = craft.launch_date.clone();
+            }
+        }
+        info
+    }
+
+    /// Details known about the person called `name`.
+    pub fn person_info(&self, name: &str) -> Option<&PersonInfo> {
+        self.people.get(&person_key(name))
+    }
+
+    /// Details known about `craft`.
+    pub fn craft(&self, craft: &str) -> Option<&CraftInfo> {
+        self.crafts.get(&Craft::new(craft))
+    }
+}
+
+/// Names are matched ignoring case and extra whitespace.
+fn person_key(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn merge(value: &mut Option<String>, other: Option<String>) {
+    if other.is_some() {
+        *value = other;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use astro_from_json;
+    use mock::fixtures;
+    use tests::temp_path;
+
+    #[test]
+    fn bundled_covers_fixtures() {
+        let metadata = Metadata::bundled();
+        let astros = astro_from_json(fixtures::ASTROS).unwrap();
+        for person in astros.people() {
+            let info = metadata.person_info(person.name()).unwrap();
+            assert!(info.agency().is_some());
+            assert!(info.launch_date().is_some());
+            assert!(metadata.craft(person.craft()).is_some());
+        }
+
+        let info = metadata.person(&Person::new(" anton  shkaplerov", "ISS"));
+        assert_eq!(info.agency(), Some("Roscosmos"));
+        assert_eq!(info.role(), Some("Commander"));
+        assert_eq!(metadata.craft("iss").unwrap().kind(), Some("Space station"));
+    }
+
+    #[test]
+    fn unknown_people() {
+        let metadata = Metadata::bundled();
+
+        let info = metadata.person(&Person::new("Jane Doe", "soyuz ms 8"));
+        assert_eq!(info.nationality(), None);
+        assert_eq!(info.launch_vehicle(), Some("Soyuz-FG"));
+
+        assert_eq!(
+            metadata.person(&Person::new("Jane Doe", "Unknown craft")),
+            PersonInfo::default()
+        );
+
+        let partial =
+            Metadata::from_json(r#"{"people": {"Jane Doe": {"launch_date": "2018-03-22"}}}"#)
+                .unwrap();
+        let info = metadata
+            .merge(partial)
+            .person(&Person::new("Jane Doe", "Soyuz MS-08"));
+        assert_eq!(info.launch_date(), Some("2018-03-22"));
+        assert_eq!(info.launch_vehicle(), Some("Soyuz-FG"));
+    }
+
+    #[test]
+    fn overrides() {
+        let overrides = Metadata::from_json(
+            r#"{"people": {
+                "Scott Tingle": {"role": "Commander"},
+                "Jane Doe": {"agency": "ESA"}},
+            "crafts": {"Soyuz MS-09": {"launch_date": "2018-06-06"}}}"#,
+        )
+        .unwrap();
+        let metadata = Metadata::bundled().merge(overrides);
+
+        let tingle = metadata.person_info("Scott Tingle").unwrap();
+        assert_eq!(tingle.role(), Some("Commander"));
+        assert_eq!(tingle.agency(), Some("NASA"));
+        assert_eq!(
+            metadata.person_info("Jane Doe").unwrap().agency(),
+            Some("ESA")
+        );
+        assert_eq!(
+            metadata
+                .person(&Person::new("John Doe", "Soyuz MS-09"))
+                .launch_date(),
+            Some("2018-06-06")
+        );
+
+        assert!(Metadata::from_json(r#"{"people": []}"#).is_err());
+    }
+
+    #[test]
+    fn override_file() {
+        let path = temp_path("metadata.json");
+        fs::write(
+            &path,
+            r#"{"people": {"Oleg Artemyev": {"role": "Commander"}}}"#,
+        )
+        .unwrap();
+
+        let metadata = Metadata::with_overrides(&path).unwrap();
+        let info = metadata.person_info("Oleg Artemyev").unwrap();
+        assert_eq!(info.role(), Some("Commander"));
+        assert_eq!(info.agency(), Some("Roscosmos"));
+
+        fs::remove_file(&path).unwrap();
+        assert!(Metadata::with_overrides(&path).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! Local stand-in for the open-notify api.
+//!
+//! `MockServer` serves `/astros.json`, `/iss-now.json` and
+//! `/iss-pass.json` on localhost so clients can be exercised end to
+//! end without network access. The responses are scriptable per path:
+//! fixtures, `"message": "failure"` replies, error status codes,
+//! malformed bodies and delays.
+//!
+//! # Example
+//! ```
+//! use open_notify_api::mock::{MockResponse, MockServer};
+//!
+//! let server = MockServer::start().unwrap();
+//! server.set("/astros.json", MockResponse::failure("maintenance"));
+//!
+//! // Point an `OpenNotifyClient` at the server with
+//! // `OpenNotifyClient::builder().base_url(&server.url())`.
+//! assert!(server.url().starts_with("http://127.0.0.1:"));
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Canned responses matching the upstream format.
+pub mod fixtures {
+    /// Successful `astros.json` response.
+    pub const ASTROS: &str = r#"{
+        "message": "success",
+        "number": 6,
+        "people": [
+        {"name": "Anton Shkaplerov", "craft": "ISS"},
+        {"name": "Scott Tingle", "craft": "ISS"},
+        {"name": "Norishige Kanai", "craft": "ISS"},
+        {"name": "Oleg Artemyev", "craft": "Soyuz MS-08"},
+        {"name": "Andrew Feustel", "craft": "Soyuz MS-08"},
+        {"name": "Richard Arnold", "craft": "Soyuz MS-08"}]
+        }"#;
+
+    /// Successful `iss-now.json` response.
+    pub const ISS_NOW: &str = r#"{
+        "iss_position": {"longitude": 73.5964, "latitude": -34.6445},
+        "message": "success",
+        "timestamp": 1521971230}"#;
+
+    /// Successful `iss-pass.json` response for
+    /// `lat=52.5&lon=13.4&alt=10&n=5`.
+    pub const ISS_PASS: &str = r#"{
+        "message": "success",
+        "request": {"latitude": 52.5, "longitude": 13.4, "altitude": 10,
+                    "passes": 5, "datetime": 1521971230},
+        "response": [
+        {"risetime": 1521985612, "duration": 496},
+        {"risetime": 1521991415, "duration": 646},
+        {"risetime": 1521997255, "duration": 615},
+        {"risetime": 1522003105, "duration": 593},
+        {"risetime": 1522008936, "duration": 621}]
+        }"#;
+}
+
+/// Scripted reply of the `MockServer`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MockResponse {
+    status: u16,
+    body: String,
+    delay: Option<Duration>,
+}
+
+impl MockResponse {
+    /// Response with the given status code and body.
+    pub fn new(status: u16, body: &str) -> MockResponse {
+        MockResponse {
+            status,
+            body: String::from(body),
+            delay: None,
+        }
+    }
+
+    /// Successful response with the given JSON body.
+    pub fn json(body: &str) -> MockResponse {
+        MockResponse::new(200, body)
+    }
+
+    /// Upstream style failure: `{"message": "failure", "reason": ...}`.
+    pub fn failure(reason: &str) -> MockResponse {
+        let body = format!(
+            r#"{{"message": "failure", "reason": {}}}"#,
+            ::serde_json::to_string(reason).unwrap()
+        );
+        MockResponse::new(200, &body)
+    }
+
+    /// Successful status with a body that is not valid JSON.
+    pub fn malformed() -> MockResponse {
+        MockResponse::new(200, r#"{"message": "success", "#)
+    }
+
+    /// Delays sending the response by `delay`.
+    pub fn delayed(mut self, delay: Duration) -> MockResponse {
+        self.delay = Some(delay);
+        self
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn body(&self) -> &str {
+        self.body.as_str()
+    }
+}
+
+#[derive(Default)]
+struct Script {
+    defaults: HashMap<String, MockResponse>,
+    queued: HashMap<String, VecDeque<MockResponse>>,
+    requests: Vec<String>,
+}
+
+impl Script {
+    fn respond(&mut self, target: &str) -> MockResponse {
+        self.requests.push(String::from(target));
+
+        let path = target.split('?').next().unwrap_or("");
+        if let Some(response) = self.queued.get_mut(path).and_then(|q| q.pop_front()) {
+            return response;
+        }
+
+        match self.defaults.get(path) {
+            Some(response) => response.clone(),
+            None => MockResponse::new(404, "not found"),
+        }
+    }
+}
+
+/// HTTP server emulating the open-notify endpoints on localhost.
+///
+/// The server listens on an ephemeral port and stops when dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    script: Arc<Mutex<Script>>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Starts a server answering all endpoints with the `fixtures`.
+    pub fn start() -> io::Result<MockServer> {
+        let server = MockServer::start_empty()?;
+        server.set("/astros.json", MockResponse::json(fixtures::ASTROS));
+        server.set("/iss-now.json", MockResponse::json(fixtures::ISS_NOW));
+        server.set("/iss-pass.json", MockResponse::json(fixtures::ISS_PASS));
+        Ok(server)
+    }
+
+    /// Starts a server answering every request with status 404
+    /// until responses are configured.
+    pub fn start_empty() -> io::Result<MockServer> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let script = Arc::new(Mutex::new(Script::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let script = script.clone();
+            let shutdown = shutdown.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let script = script.clone();
+                        thread::spawn(move || {
+                            let _ = serve(stream, &script);
+                        });
+                    }
+                }
+            })
+        };
+
+        Ok(MockServer {
+            addr,
+            script,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// Address the server listens on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Base url to configure clients with.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Answers every request for `path` with `response`.
+    ///
+    /// The query string is ignored when matching `path`.
+    pub fn set(&self, path: &str, response: MockResponse) {
+        let mut script = self.script.lock().unwrap();
+        script.defaults.insert(String::from(path), response);
+    }
+
+    /// Answers the next request for `path` with `response`.
+    ///
+    /// Queued responses are used once, in order, before falling back
+    /// to the response configured with `set`.
+    pub fn enqueue(&self, path: &str, response: MockResponse) {
+        let mut script = self.script.lock().unwrap();
+        script
+            .queued
+            .entry(String::from(path))
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Request targets (path and query) received so far, oldest first.
+    pub fn requests(&self) -> Vec<String> {
+        self.script.lock().unwrap().requests.clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the accept loop so it notices the shutdown.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn serve(stream: TcpStream, script: &Mutex<Script>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let response = script.lock().unwrap().respond(target);
+
+    if let Some(delay) = response.delay {
+        thread::sleep(delay);
+    }
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason_phrase(response.status),
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_queued_before_default() {
+        let mut script = Script::default();
+        script
+            .defaults
+            .insert(String::from("/iss-now.json"), MockResponse::json("{}"));
+        script
+            .queued
+            .entry(String::from("/iss-now.json"))
+            .or_default()
+            .push_back(MockResponse::new(503, "down"));
+
+        assert_eq!(script.respond("/iss-now.json").status(), 503);
+        assert_eq!(script.respond("/iss-now.json").status(), 200);
+        assert_eq!(script.respond("/iss-pass.json?n=1").status(), 404);
+        assert_eq!(
+            script.requests,
+            vec!["/iss-now.json", "/iss-now.json", "/iss-pass.json?n=1"]
+        );
+    }
+
+    #[test]
+    fn failure_response_escapes_reason() {
+        let response = MockResponse::failure(r#"bad "lat""#);
+        assert_eq!(
+            response.body(),
+            r#"{"message": "failure", "reason": "bad \"lat\""}"#
+        );
+    }
+}
@@ -0,0 +1,847 @@
+//! Local orbit propagation from Two-Line Element sets.
+//!
+//! `Tle` parses the element sets published e.g. by CelesTrak and
+//! `Sgp4` propagates them to any instant with the SGP4 model. Only
+//! near earth orbits (period below 225 minutes) are supported, which
+//! covers the ISS.
+//!
+//! # Example
+//! ```
+//! use open_notify_api::orbit::{Sgp4, Tle};
+//!
+//! let tle = Tle::parse(
+//!     "1 25544U 98067A   18084.88124977  .00001628  00000-0  31711-4 0  9991",
+//!     "2 25544  51.6416 359.2614 0001944 125.2585 357.1463 15.54190618105837",
+//! ).unwrap();
+//! let sgp4 = Sgp4::new(&tle).unwrap();
+//!
+//! let position = sgp4.position(1521971230).unwrap();
+//! assert!(position.latitude().abs() <= 51.7);
+//! assert!(position.altitude() > 350.0 && position.altitude() < 450.0);
+//! ```
+
+use std::f64::consts::PI;
+
+use error::OpenNotificationError;
+use {IssNow, IssPosition};
+
+const TWO_PI: f64 = 2.0 * PI;
+const MINUTES_PER_DAY: f64 = 1440.0;
+
+// WGS72 constants used by SGP4.
+const MU: f64 = 398600.8;
+const EARTH_RADIUS: f64 = 6378.135;
+const J2: f64 = 0.001082616;
+const J3: f64 = -0.00000253881;
+const J4: f64 = -0.00000165597;
+
+// WGS84 ellipsoid used for geodetic coordinates.
+const WGS84_A: f64 = 6378.137;
+const WGS84_F: f64 = 1.0 / 298.257223563;
+
+fn xke() -> f64 {
+    60.0 / (EARTH_RADIUS * EARTH_RADIUS * EARTH_RADIUS / MU).sqrt()
+}
+
+/// Two-Line Element set describing an orbit at an epoch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tle {
+    name: Option<String>,
+    catalog_number: u32,
+    epoch: f64,
+    bstar: f64,
+    inclination: f64,
+    right_ascension: f64,
+    eccentricity: f64,
+    argument_of_perigee: f64,
+    mean_anomaly: f64,
+    mean_motion: f64,
+}
+
+impl Tle {
+    /// Parses the two data lines of an element set.
+    ///
+    /// Checksums are verified. Malformed lines are reported as
+    /// `OpenNotificationError::Data`.
+    pub fn parse(line1: &str, line2: &str) -> Result<Tle, OpenNotificationError> {
+        let line1 = line1.trim_end();
+        let line2 = line2.trim_end();
+
+        check_line(line1, '1')?;
+        check_line(line2, '2')?;
+
+        let catalog_number = field(line1, 2, 7)?;
+        if field::<u32>(line2, 2, 7)? != catalog_number {
+            return Err(tle_error("catalog numbers of line 1 and 2 differ"));
+        }
+
+        let year: i32 = field(line1, 18, 20)?;
+        let year = if year < 57 { 2000 + year } else { 1900 + year };
+        let day: f64 = field(line1, 20, 32)?;
+
+        Ok(Tle {
+            name: None,
+            catalog_number,
+            epoch: days_from_civil(year, 1, 1) as f64 * 86400.0 + (day - 1.0) * 86400.0,
+            bstar: exponent_field(line1, 53, 61)?,
+            inclination: field(line2, 8, 16)?,
+            right_ascension: field(line2, 17, 25)?,
+            eccentricity: field::<f64>(line2, 26, 33)? / 1e7,
+            argument_of_perigee: field(line2, 34, 42)?,
+            mean_anomaly: field(line2, 43, 51)?,
+            mean_motion: field(line2, 52, 63)?,
+        })
+    }
+
+    /// Parses an element set with an optional leading name line.
+    pub fn parse_str(data: &str) -> Result<Tle, OpenNotificationError> {
+        let lines: Vec<&str> = data.lines().filter(|l| !l.trim().is_empty()).collect();
+        match lines.len() {
+            2 => Tle::parse(lines[0], lines[1]),
+            3 => {
+                let mut tle = Tle::parse(lines[1], lines[2])?;
+                let name = lines[0].trim().trim_start_matches("0 ");
+                tle.name = Some(String::from(name));
+                Ok(tle)
+            }
+            _ => Err(tle_error("expected two or three lines")),
+        }
+    }
+
+    /// Satellite name, if the element set had a name line.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// NORAD catalog number, 25544 for the ISS.
+    pub fn catalog_number(&self) -> u32 {
+        self.catalog_number
+    }
+
+    /// Epoch of the element set as unix timestamp in seconds.
+    pub fn epoch(&self) -> f64 {
+        self.epoch
+    }
+
+    /// Inclination in degrees
+    pub fn inclination(&self) -> f64 {
+        self.inclination
+    }
+
+    /// Eccentricity
+    pub fn eccentricity(&self) -> f64 {
+        self.eccentricity
+    }
+
+    /// Mean motion in revolutions per day
+    pub fn mean_motion(&self) -> f64 {
+        self.mean_motion
+    }
+}
+
+/// Geodetic position and speed of a satellite at an instant.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    timestamp: i64,
+    latitude: f64,
+    longitude: f64,
+    altitude: f64,
+    velocity: f64,
+}
+
+impl Position {
+    /// Unix timestamp the position was computed for.
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    /// Geodetic latitude in degrees
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    /// Longitude in degrees, -180 to 180
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    /// Altitude above the WGS84 ellipsoid in kilometers
+    pub fn altitude(&self) -> f64 {
+        self.altitude
+    }
+
+    /// Orbital velocity in kilometers per second
+    pub fn velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    /// Converts the position into an `IssNow` as the api
+    /// would report it.
+    pub fn to_iss_now(&self) -> IssNow {
+        IssNow {
+            message: String::from("success"),
+            reason: String::new(),
+            timestamp: self.timestamp,
+            iss_position: IssPosition {
+                latitude: self.latitude as f32,
+                longitude: self.longitude as f32,
+            },
+        }
+    }
+}
+
+/// Position and velocity in the True Equator Mean Equinox frame,
+/// in kilometers and kilometers per second.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct StateVector {
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+}
+
+/// SGP4 propagator initialized from a `Tle`.
+#[derive(Clone, Debug)]
+pub struct Sgp4 {
+    epoch: f64,
+    bstar: f64,
+    ecco: f64,
+    argpo: f64,
+    inclo: f64,
+    mo: f64,
+    no: f64,
+    nodeo: f64,
+    isimp: bool,
+    aycof: f64,
+    con41: f64,
+    cc1: f64,
+    cc4: f64,
+    cc5: f64,
+    d2: f64,
+    d3: f64,
+    d4: f64,
+    delmo: f64,
+    eta: f64,
+    argpdot: f64,
+    omgcof: f64,
+    sinmao: f64,
+    t2cof: f64,
+    t3cof: f64,
+    t4cof: f64,
+    t5cof: f64,
+    x1mth2: f64,
+    x7thm1: f64,
+    mdot: f64,
+    nodedot: f64,
+    xlcof: f64,
+    xmcof: f64,
+    nodecf: f64,
+}
+
+impl Sgp4 {
+    /// Initializes the propagator.
+    ///
+    /// Deep space orbits are rejected with
+    /// `OpenNotificationError::Data`.
+    pub fn new(tle: &Tle) -> Result<Sgp4, OpenNotificationError> {
+        let deg = PI / 180.0;
+        let xke = xke();
+        let x2o3 = 2.0 / 3.0;
+        let j3oj2 = J3 / J2;
+
+        let ecco = tle.eccentricity;
+        let inclo = tle.inclination * deg;
+        let argpo = tle.argument_of_perigee * deg;
+        let mo = tle.mean_anomaly * deg;
+        let nodeo = tle.right_ascension * deg;
+        let no_kozai = tle.mean_motion * TWO_PI / MINUTES_PER_DAY;
+        let bstar = tle.bstar;
+
+        if no_kozai <= 0.0 || !(0.0..1.0).contains(&ecco) {
+            return Err(tle_error("mean motion or eccentricity out of range"));
+        }
+
+        // Recover the original mean motion and semi-major axis.
+        let eccsq = ecco * ecco;
+        let omeosq = 1.0 - eccsq;
+        let rteosq = omeosq.sqrt();
+        let cosio = inclo.cos();
+        let cosio2 = cosio * cosio;
+        let ak = (xke / no_kozai).powf(x2o3);
+        let d1 = 0.75 * J2 * (3.0 * cosio2 - 1.0) / (rteosq * omeosq);
+        let del = d1 / (ak * ak);
+        let adel = ak * (1.0 - del * del - del * (1.0 / 3.0 + 134.0 * del * del / 81.0));
+        let del = d1 / (adel * adel);
+        let no = no_kozai / (1.0 + del);
+
+        if TWO_PI / no >= 225.0 {
+            return Err(tle_error("deep space orbits are not supported"));
+        }
+
+        let ao = (xke / no).powf(x2o3);
+        let sinio = inclo.sin();
+        let po = ao * omeosq;
+        let con42 = 1.0 - 5.0 * cosio2;
+        let con41 = -con42 - cosio2 - cosio2;
+        let posq = po * po;
+        let rp = ao * (1.0 - ecco);
+
+        let isimp = rp < 220.0 / EARTH_RADIUS + 1.0;
+        let mut sfour = 78.0 / EARTH_RADIUS + 1.0;
+        let mut qzms24 = ((120.0 - 78.0) / EARTH_RADIUS).powi(4);
+        let perige = (rp - 1.0) * EARTH_RADIUS;
+        if perige < 156.0 {
+            sfour = if perige < 98.0 { 20.0 } else { perige - 78.0 };
+            qzms24 = ((120.0 - sfour) / EARTH_RADIUS).powi(4);
+            sfour = sfour / EARTH_RADIUS + 1.0;
+        }
+
+        let pinvsq = 1.0 / posq;
+        let tsi = 1.0 / (ao - sfour);
+        let eta = ao * ecco * tsi;
+        let etasq = eta * eta;
+        let eeta = ecco * eta;
+        let psisq = (1.0 - etasq).abs();
+        let coef = qzms24 * tsi.powi(4);
+        let coef1 = coef / psisq.powf(3.5);
+        let cc2 = coef1
+            * no
+            * (ao * (1.0 + 1.5 * etasq + eeta * (4.0 + etasq))
+                + 0.375 * J2 * tsi / psisq * con41 * (8.0 + 3.0 * etasq * (8.0 + etasq)));
+        let cc1 = bstar * cc2;
+        let cc3 = if ecco > 1.0e-4 {
+            -2.0 * coef * tsi * j3oj2 * no * sinio / ecco
+        } else {
+            0.0
+        };
+        let x1mth2 = 1.0 - cosio2;
+        let cc4 = 2.0
+            * no
+            * coef1
+            * ao
+            * omeosq
+            * (eta * (2.0 + 0.5 * etasq) + ecco * (0.5 + 2.0 * etasq)
+                - J2 * tsi / (ao * psisq)
+                    * (-3.0 * con41 * (1.0 - 2.0 * eeta + etasq * (1.5 - 0.5 * eeta))
+                        + 0.75
+                            * x1mth2
+                            * (2.0 * etasq - eeta * (1.0 + etasq))
+                            * (2.0 * argpo).cos()));
+        let cc5 = 2.0 * coef1 * ao * omeosq * (1.0 + 2.75 * (etasq + eeta) + eeta * etasq);
+
+        let cosio4 = cosio2 * cosio2;
+        let temp1 = 1.5 * J2 * pinvsq * no;
+        let temp2 = 0.5 * temp1 * J2 * pinvsq;
+        let temp3 = -0.46875 * J4 * pinvsq * pinvsq * no;
+        let mdot = no
+            + 0.5 * temp1 * rteosq * con41
+            + 0.0625 * temp2 * rteosq * (13.0 - 78.0 * cosio2 + 137.0 * cosio4);
+        let argpdot = -0.5 * temp1 * con42
+            + 0.0625 * temp2 * (7.0 - 114.0 * cosio2 + 395.0 * cosio4)
+            + temp3 * (3.0 - 36.0 * cosio2 + 49.0 * cosio4);
+        let xhdot1 = -temp1 * cosio;
+        let nodedot = xhdot1
+            + (0.5 * temp2 * (4.0 - 19.0 * cosio2) + 2.0 * temp3 * (3.0 - 7.0 * cosio2)) * cosio;
+        let omgcof = bstar * cc3 * argpo.cos();
+        let xmcof = if ecco > 1.0e-4 {
+            -x2o3 * coef * bstar / eeta
+        } else {
+            0.0
+        };
+        let nodecf = 3.5 * omeosq * xhdot1 * cc1;
+        let t2cof = 1.5 * cc1;
+        let xlcof = if (cosio + 1.0).abs() > 1.5e-12 {
+            -0.25 * j3oj2 * sinio * (3.0 + 5.0 * cosio) / (1.0 + cosio)
+        } else {
+            -0.25 * j3oj2 * sinio * (3.0 + 5.0 * cosio) / 1.5e-12
+        };
+        let aycof = -0.5 * j3oj2 * sinio;
+        let delmo = (1.0 + eta * mo.cos()).powi(3);
+        let sinmao = mo.sin();
+        let x7thm1 = 7.0 * cosio2 - 1.0;
+
+        let (mut d2, mut d3, mut d4) = (0.0, 0.0, 0.0);
+        let (mut t3cof, mut t4cof, mut t5cof) = (0.0, 0.0, 0.0);
+        if !isimp {
+            let cc1sq = cc1 * cc1;
+            d2 = 4.0 * ao * tsi * cc1sq;
+            let temp = d2 * tsi * cc1 / 3.0;
+            d3 = (17.0 * ao + sfour) * temp;
+            d4 = 0.5 * temp * ao * tsi * (221.0 * ao + 31.0 * sfour) * cc1;
+            t3cof = d2 + 2.0 * cc1sq;
+            t4cof = 0.25 * (3.0 * d3 + cc1 * (12.0 * d2 + 10.0 * cc1sq));
+            t5cof = 0.2
+                * (3.0 * d4 + 12.0 * cc1 * d3 + 6.0 * d2 * d2 + 15.0 * cc1sq * (2.0 * d2 + cc1sq));
+        }
+
+        Ok(Sgp4 {
+            epoch: tle.epoch,
+            bstar,
+            ecco,
+            argpo,
+            inclo,
+            mo,
+            no,
+            nodeo,
+            isimp,
+            aycof,
+            con41,
+            cc1,
+            cc4,
+            cc5,
+            d2,
+            d3,
+            d4,
+            delmo,
+            eta,
+            argpdot,
+            omgcof,
+            sinmao,
+            t2cof,
+            t3cof,
+            t4cof,
+            t5cof,
+            x1mth2,
+            x7thm1,
+            mdot,
+            nodedot,
+            xlcof,
+            xmcof,
+            nodecf,
+        })
+    }
+
+    /// Epoch of the underlying element set as unix timestamp.
+    pub fn epoch(&self) -> f64 {
+        self.epoch
+    }
+
+    /// Orbital period in seconds.
+    pub fn period(&self) -> f64 {
+        TWO_PI / self.no * 60.0
+    }
+
+    /// Geodetic position at the unix `timestamp`.
+    ///
+    /// Fails with `OpenNotificationError::Data` if the orbit has
+    /// decayed at that time.
+    pub fn position(&self, timestamp: i64) -> Result<Position, OpenNotificationError> {
+        self.position_at(timestamp as f64)
+            .map(|(position, _)| position)
+    }
+
+    /// Geodetic position at a fractional unix timestamp, along with
+    /// the position of the satellite in earth fixed coordinates (km).
+    pub(crate) fn position_at(
+        &self,
+        timestamp: f64,
+    ) -> Result<(Position, [f64; 3]), OpenNotificationError> {
+        let state = self.state_at(timestamp)?;
+        let ecef = teme_to_ecef(&state.position, timestamp);
+        let (latitude, longitude, altitude) = ecef_to_geodetic(&ecef);
+        let v = state.velocity;
+
+        Ok((
+            Position {
+                timestamp: timestamp.round() as i64,
+                latitude,
+                longitude,
+                altitude,
+                velocity: (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt(),
+            },
+            ecef,
+        ))
+    }
+
+    /// TEME state vector at a fractional unix timestamp.
+    pub(crate) fn state_at(&self, timestamp: f64) -> Result<StateVector, OpenNotificationError> {
+        self.propagate((timestamp - self.epoch) / 60.0)
+    }
+
+    /// TEME state vector `t` minutes after the epoch.
+    fn propagate(&self, t: f64) -> Result<StateVector, OpenNotificationError> {
+        let xke = xke();
+        let x2o3 = 2.0 / 3.0;
+
+        let xmdf = self.mo + self.mdot * t;
+        let argpdf = self.argpo + self.argpdot * t;
+        let nodedf = self.nodeo + self.nodedot * t;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let t2 = t * t;
+        let mut nodem = nodedf + self.nodecf * t2;
+        let mut tempa = 1.0 - self.cc1 * t;
+        let mut tempe = self.bstar * self.cc4 * t;
+        let mut templ = self.t2cof * t2;
+
+        if !self.isimp {
+            let delomg = self.omgcof * t;
+            let delm = self.xmcof * ((1.0 + self.eta * xmdf.cos()).powi(3) - self.delmo);
+            let temp = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3 = t2 * t;
+            let t4 = t3 * t;
+            tempa = tempa - self.d2 * t2 - self.d3 * t3 - self.d4 * t4;
+            tempe += self.bstar * self.cc5 * (mm.sin() - self.sinmao);
+            templ += self.t3cof * t3 + t4 * (self.t4cof + t * self.t5cof);
+        }
+
+        let am = (xke / self.no).powf(x2o3) * tempa * tempa;
+        let nm = xke / am.powf(1.5);
+        let mut em = self.ecco - tempe;
+        if !(-0.001..1.0).contains(&em) {
+            return Err(decayed());
+        }
+        if em < 1.0e-6 {
+            em = 1.0e-6;
+        }
+        mm += self.no * templ;
+        let mut xlm = mm + argpm + nodem;
+
+        nodem %= TWO_PI;
+        argpm %= TWO_PI;
+        xlm %= TWO_PI;
+        mm = (xlm - argpm - nodem) % TWO_PI;
+
+        let sinip = self.inclo.sin();
+        let cosip = self.inclo.cos();
+
+        // Long period periodics
+        let axnl = em * argpm.cos();
+        let temp = 1.0 / (am * (1.0 - em * em));
+        let aynl = em * argpm.sin() + temp * self.aycof;
+        let xl = mm + argpm + nodem + temp * self.xlcof * axnl;
+
+        // Solve Kepler's equation
+        let u = (xl - nodem) % TWO_PI;
+        let mut eo1 = u;
+        let mut tem5: f64 = 9999.9;
+        let mut sineo1 = 0.0;
+        let mut coseo1 = 0.0;
+        let mut ktr = 1;
+        while tem5.abs() >= 1.0e-12 && ktr <= 10 {
+            sineo1 = eo1.sin();
+            coseo1 = eo1.cos();
+            tem5 = 1.0 - coseo1 * axnl - sineo1 * aynl;
+            tem5 = (u - aynl * coseo1 + axnl * sineo1 - eo1) / tem5;
+            if tem5.abs() >= 0.95 {
+                tem5 = 0.95_f64.copysign(tem5);
+            }
+            eo1 += tem5;
+            ktr += 1;
+        }
+
+        // Short period preliminary quantities
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (1.0 - el2);
+        if pl < 0.0 {
+            return Err(decayed());
+        }
+        let rl = am * (1.0 - ecose);
+        let rdotl = am.sqrt() * esine / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (1.0 - el2).sqrt();
+        let temp = esine / (1.0 + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let mut su = sinu.atan2(cosu);
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = 1.0 - 2.0 * sinu * sinu;
+        let temp = 1.0 / pl;
+        let temp1 = 0.5 * J2 * temp;
+        let temp2 = temp1 * temp;
+
+        // Update for short period periodics
+        let mrt = rl * (1.0 - 1.5 * temp2 * betal * self.con41) + 0.5 * temp1 * self.x1mth2 * cos2u;
+        su -= 0.25 * temp2 * self.x7thm1 * sin2u;
+        let xnode = nodem + 1.5 * temp2 * cosip * sin2u;
+        let xinc = self.inclo + 1.5 * temp2 * cosip * sinip * cos2u;
+        let mvt = rdotl - nm * temp1 * self.x1mth2 * sin2u / xke;
+        let rvdot = rvdotl + nm * temp1 * (self.x1mth2 * cos2u + 1.5 * self.con41) / xke;
+
+        if mrt < 1.0 {
+            return Err(decayed());
+        }
+
+        // Orientation vectors
+        let (sinsu, cossu) = su.sin_cos();
+        let (snod, cnod) = xnode.sin_cos();
+        let (sini, cosi) = xinc.sin_cos();
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let ux = xmx * sinsu + cnod * cossu;
+        let uy = xmy * sinsu + snod * cossu;
+        let uz = sini * sinsu;
+        let vx = xmx * cossu - cnod * sinsu;
+        let vy = xmy * cossu - snod * sinsu;
+        let vz = sini * cossu;
+
+        let vkmpersec = EARTH_RADIUS * xke / 60.0;
+        Ok(StateVector {
+            position: [
+                mrt * ux * EARTH_RADIUS,
+                mrt * uy * EARTH_RADIUS,
+                mrt * uz * EARTH_RADIUS,
+            ],
+            velocity: [
+                (mvt * ux + rvdot * vx) * vkmpersec,
+                (mvt * uy + rvdot * vy) * vkmpersec,
+                (mvt * uz + rvdot * vz) * vkmpersec,
+            ],
+        })
+    }
+}
+
+/// Greenwich mean sidereal time in radians at a unix timestamp.
+pub(crate) fn gmst(timestamp: f64) -> f64 {
+    let jd = timestamp / 86400.0 + 2440587.5;
+    let tut1 = (jd - 2451545.0) / 36525.0;
+    let seconds = -6.2e-6 * tut1 * tut1 * tut1
+        + 0.093104 * tut1 * tut1
+        + (876600.0 * 3600.0 + 8640184.812866) * tut1
+        + 67310.54841;
+    let gmst = (seconds * PI / 180.0 / 240.0) % TWO_PI;
+    if gmst < 0.0 {
+        gmst + TWO_PI
+    } else {
+        gmst
+    }
+}
+
+/// Rotates a TEME vector into the earth fixed frame, ignoring polar motion.
+pub(crate) fn teme_to_ecef(teme: &[f64; 3], timestamp: f64) -> [f64; 3] {
+    let (sin, cos) = gmst(timestamp).sin_cos();
+    [
+        cos * teme[0] + sin * teme[1],
+        -sin * teme[0] + cos * teme[1],
+        teme[2],
+    ]
+}
+
+/// Converts earth fixed coordinates (km) into geodetic latitude and
+/// longitude in degrees and altitude in km above the WGS84 ellipsoid.
+pub(crate) fn ecef_to_geodetic(ecef: &[f64; 3]) -> (f64, f64, f64) {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let p = (ecef[0] * ecef[0] + ecef[1] * ecef[1]).sqrt();
+    let longitude = ecef[1].atan2(ecef[0]);
+
+    let mut latitude = ecef[2].atan2(p * (1.0 - e2));
+    let mut altitude = 0.0;
+    for _ in 0..5 {
+        let sin = latitude.sin();
+        let n = WGS84_A / (1.0 - e2 * sin * sin).sqrt();
+        altitude = p / latitude.cos() - n;
+        latitude = ecef[2].atan2(p * (1.0 - e2 * n / (n + altitude)));
+    }
+
+    (latitude.to_degrees(), longitude.to_degrees(), altitude)
+}
+
+/// Converts geodetic coordinates (degrees, km) into earth fixed
+/// coordinates (km).
+pub(crate) fn geodetic_to_ecef(latitude: f64, longitude: f64, altitude: f64) -> [f64; 3] {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let (sin_lat, cos_lat) = latitude.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = longitude.to_radians().sin_cos();
+    let n = WGS84_A / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+    [
+        (n + altitude) * cos_lat * cos_lon,
+        (n + altitude) * cos_lat * sin_lon,
+        (n * (1.0 - e2) + altitude) * sin_lat,
+    ]
+}
+
+/// Days between 1970-01-01 and the given date of the
+/// proleptic Gregorian calendar.
+fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year } as i64;
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let month = month as i64;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn check_line(line: &str, number: char) -> Result<(), OpenNotificationError> {
+    if line.len() != 69 || !line.is_ascii() || !line.starts_with(number) {
+        return Err(tle_error(&format!("line {} is malformed", number)));
+    }
+
+    let checksum = line[..68].chars().fold(0, |sum, c| match c {
+        '-' => sum + 1,
+        _ => sum + c.to_digit(10).unwrap_or(0),
+    }) % 10;
+    if line[68..].parse::<u32>().ok() != Some(checksum) {
+        return Err(tle_error(&format!(
+            "checksum of line {} does not match",
+            number
+        )));
+    }
+
+    Ok(())
+}
+
+fn field<T: ::std::str::FromStr>(
+    line: &str,
+    start: usize,
+    end: usize,
+) -> Result<T, OpenNotificationError> {
+    line[start..end]
+        .trim()
+        .parse()
+        .map_err(|_| tle_error(&format!("invalid field '{}'", &line[start..end])))
+}
+
+/// Parses fields in the assumed decimal point notation, e.g.
+/// ` 31711-4` for 0.31711e-4.
+fn exponent_field(line: &str, start: usize, end: usize) -> Result<f64, OpenNotificationError> {
+    let raw = line[start..end].trim();
+    if raw.is_empty() {
+        return Ok(0.0);
+    }
+
+    let split = raw
+        .rfind(['-', '+'])
+        .filter(|&i| i > 0)
+        .ok_or_else(|| tle_error(&format!("invalid field '{}'", raw)))?;
+    let (mantissa, exponent) = raw.split_at(split);
+    let (sign, mantissa) = if let Some(stripped) = mantissa.strip_prefix('-') {
+        (-1.0, stripped)
+    } else {
+        (1.0, mantissa.trim_start_matches('+'))
+    };
+
+    let mantissa: f64 = format!("0.{}", mantissa)
+        .parse()
+        .map_err(|_| tle_error(&format!("invalid field '{}'", raw)))?;
+    let exponent: i32 = exponent
+        .parse()
+        .map_err(|_| tle_error(&format!("invalid field '{}'", raw)))?;
+
+    Ok(sign * mantissa * 10f64.powi(exponent))
+}
+
+fn tle_error(msg: &str) -> OpenNotificationError {
+    OpenNotificationError::Data(format!("invalid TLE: {}", msg))
+}
+
+fn decayed() -> OpenNotificationError {
+    OpenNotificationError::Data(String::from("orbit has decayed"))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub const ISS_LINE1: &str =
+        "1 25544U 98067A   18084.88124977  .00001628  00000-0  31711-4 0  9991";
+    pub const ISS_LINE2: &str =
+        "2 25544  51.6416 359.2614 0001944 125.2585 357.1463 15.54190618105837";
+
+    pub fn iss_sgp4() -> Sgp4 {
+        Sgp4::new(&Tle::parse(ISS_LINE1, ISS_LINE2).unwrap()).unwrap()
+    }
+
+    // Test case 00005 of the SGP4 verification data set
+    // published with "Revisiting Spacetrack Report #3".
+    const VANGUARD_LINE1: &str =
+        "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
+    const VANGUARD_LINE2: &str =
+        "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+    fn assert_close(actual: &[f64; 3], expected: &[f64; 3], tolerance: f64) {
+        for i in 0..3 {
+            assert!(
+                (actual[i] - expected[i]).abs() < tolerance,
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn tle_parse() {
+        let tle = Tle::parse_str(&format!("ISS (ZARYA)\n{}\n{}\n", ISS_LINE1, ISS_LINE2)).unwrap();
+        assert_eq!(tle.name(), Some("ISS (ZARYA)"));
+        assert_eq!(tle.catalog_number(), 25544);
+        assert_eq!(tle.inclination(), 51.6416);
+        assert_eq!(tle.eccentricity(), 0.0001944);
+        assert_eq!(tle.mean_motion(), 15.54190618);
+        assert!((tle.bstar - 0.31711e-4).abs() < 1e-12);
+        // 2018-03-25T21:08:59.98 UTC
+        assert!((tle.epoch() - 1522012139.98).abs() < 0.01);
+    }
+
+    #[test]
+    fn tle_parse_invalid_checksum() {
+        let line1 = ISS_LINE1.replace("9991", "9992");
+        match Tle::parse(&line1, ISS_LINE2) {
+            Err(OpenNotificationError::Data(_)) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn sgp4_verification_vector() {
+        let tle = Tle::parse(VANGUARD_LINE1, VANGUARD_LINE2).unwrap();
+        let sgp4 = Sgp4::new(&tle).unwrap();
+
+        let state = sgp4.propagate(0.0).unwrap();
+        assert_close(
+            &state.position,
+            &[7022.46529266, -1400.08296755, 0.03995155],
+            1e-3,
+        );
+        assert_close(
+            &state.velocity,
+            &[1.893841015, 6.405893759, 4.534807250],
+            1e-6,
+        );
+
+        let state = sgp4.propagate(360.0).unwrap();
+        assert_close(
+            &state.position,
+            &[-7154.03120202, -3783.17682504, -3536.19412294],
+            1e-3,
+        );
+        assert_close(
+            &state.velocity,
+            &[4.741887409, -4.151817765, -2.093935425],
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn sgp4_iss_position() {
+        let sgp4 = iss_sgp4();
+        let position = sgp4.position(1522012140).unwrap();
+
+        assert!(position.latitude().abs() <= 51.7);
+        assert!(position.longitude().abs() <= 180.0);
+        assert!(position.altitude() > 390.0 && position.altitude() < 430.0);
+        assert!(position.velocity() > 7.6 && position.velocity() < 7.7);
+
+        let iss_now = position.to_iss_now();
+        assert_eq!(iss_now.timestamp(), 1522012140);
+        assert_eq!(iss_now.latitude(), position.latitude() as f32);
+    }
+
+    #[test]
+    fn geodetic_round_trip() {
+        let ecef = geodetic_to_ecef(52.5, 13.4, 0.034);
+        let (latitude, longitude, altitude) = ecef_to_geodetic(&ecef);
+        assert!((latitude - 52.5).abs() < 1e-9);
+        assert!((longitude - 13.4).abs() < 1e-9);
+        assert!((altitude - 0.034).abs() < 1e-6);
+    }
+
+    #[test]
+    fn civil_days() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 1, 1), 10957);
+        assert_eq!(days_from_civil(2018, 3, 25), 17615);
+    }
+}
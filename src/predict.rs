@@ -0,0 +1,582 @@
+//! Offline prediction of ISS passes over an observer.
+//!
+//! Passes are computed from a local `Sgp4` propagator, so no
+//! connection to open-notify is needed and the number of passes is
+//! only limited by the search window.
+//!
+//! # Example
+//! ```
+//! use open_notify_api::orbit::{Sgp4, Tle};
+//! use open_notify_api::predict::{Observer, PassPredictor};
+//!
+//! let tle = Tle::parse(
+//!     "1 25544U 98067A   18084.88124977  .00001628  00000-0  31711-4 0  9991",
+//!     "2 25544  51.6416 359.2614 0001944 125.2585 357.1463 15.54190618105837",
+//! ).unwrap();
+//! let sgp4 = Sgp4::new(&tle).unwrap();
+//! let berlin = Observer::new(52.5, 13.4, 34.0).unwrap();
+//!
+//! let predictor = PassPredictor::new(&sgp4, berlin);
+//! for pass in predictor.passes(1522012140, 1522012140 + 86400).unwrap() {
+//!     println!("{} for {} seconds, up to {:.0}°",
+//!         pass.rise(), pass.duration(), pass.max_elevation());
+//! }
+//! ```
+
+use error::OpenNotificationError;
+use orbit::{geodetic_to_ecef, teme_to_ecef, Sgp4};
+use sun::{is_sunlit, sun_position, Daylight};
+use {IssPassTime, IssPassTimes};
+
+/// Interval the elevation is sampled at while searching for passes.
+/// Short enough not to miss passes barely above the minimum elevation.
+const SEARCH_STEP: i64 = 20;
+
+/// Longest time span `next_passes` searches before giving up.
+const MAX_SEARCH: i64 = 30 * 86400;
+
+/// Interval a pass is sampled at to determine its visibility.
+const VISIBILITY_STEP: i64 = 10;
+
+/// Whether a pass can be seen with the naked eye.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    /// The ISS is lit by the sun while the observer is in twilight
+    /// or darkness for at least a part of the pass.
+    Visible,
+    /// The observer is in daylight during the whole pass.
+    Daylight,
+    /// The observer is in twilight or darkness, but the ISS is in
+    /// the earth's shadow during the whole pass.
+    Eclipsed,
+}
+
+/// Location on the ground passes are predicted for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Observer {
+    latitude: f64,
+    longitude: f64,
+    altitude: f64,
+}
+
+impl Observer {
+    /// Observer at `latitude` (-90 to 90 degrees), `longitude`
+    /// (-180 to 180 degrees) and `altitude` in meters.
+    pub fn new(
+        latitude: f64,
+        longitude: f64,
+        altitude: f64,
+    ) -> Result<Observer, OpenNotificationError> {
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(OpenNotificationError::InvalidArgument(format!(
+                "latitude {} is out of range -90 to 90",
+                latitude
+            )));
+        }
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err(OpenNotificationError::InvalidArgument(format!(
+                "longitude {} is out of range -180 to 180",
+                longitude
+            )));
+        }
+        if !altitude.is_finite() {
+            return Err(OpenNotificationError::InvalidArgument(format!(
+                "altitude {} is not a number",
+                altitude
+            )));
+        }
+
+        Ok(Observer {
+            latitude,
+            longitude,
+            altitude,
+        })
+    }
+
+    /// Latitude in degrees
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    /// Longitude in degrees
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    /// Altitude in meters
+    pub fn altitude(&self) -> f64 {
+        self.altitude
+    }
+
+    /// Azimuth and elevation in degrees and range in kilometers of
+    /// a point given in earth fixed coordinates (km).
+    pub(crate) fn look_angles(&self, target: &[f64; 3]) -> LookAngles {
+        let site = geodetic_to_ecef(self.latitude, self.longitude, self.altitude / 1000.0);
+        let d = [
+            target[0] - site[0],
+            target[1] - site[1],
+            target[2] - site[2],
+        ];
+
+        let (sin_lat, cos_lat) = self.latitude.to_radians().sin_cos();
+        let (sin_lon, cos_lon) = self.longitude.to_radians().sin_cos();
+        let east = -sin_lon * d[0] + cos_lon * d[1];
+        let north = -sin_lat * cos_lon * d[0] - sin_lat * sin_lon * d[1] + cos_lat * d[2];
+        let up = cos_lat * cos_lon * d[0] + cos_lat * sin_lon * d[1] + sin_lat * d[2];
+
+        let azimuth = east.atan2(north).to_degrees();
+        LookAngles {
+            azimuth: if azimuth < 0.0 {
+                azimuth + 360.0
+            } else {
+                azimuth
+            },
+            elevation: up.atan2((east * east + north * north).sqrt()).to_degrees(),
+            range: (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct LookAngles {
+    pub azimuth: f64,
+    pub elevation: f64,
+    pub range: f64,
+}
+
+/// A predicted pass of the ISS over an observer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PredictedPass {
+    rise: i64,
+    set: i64,
+    culmination: i64,
+    max_elevation: f64,
+    rise_azimuth: f64,
+    set_azimuth: f64,
+    visibility: Visibility,
+}
+
+impl PredictedPass {
+    /// Unix timestamp the ISS rises above the minimum elevation
+    pub fn rise(&self) -> i64 {
+        self.rise
+    }
+
+    /// Seconds the ISS stays above the minimum elevation
+    pub fn duration(&self) -> i64 {
+        self.set - self.rise
+    }
+
+    /// Unix timestamp the ISS sets below the minimum elevation
+    pub fn set_time(&self) -> i64 {
+        self.set
+    }
+
+    /// Unix timestamp of the highest elevation
+    pub fn culmination(&self) -> i64 {
+        self.culmination
+    }
+
+    /// Highest elevation above the horizon in degrees
+    pub fn max_elevation(&self) -> f64 {
+        self.max_elevation
+    }
+
+    /// Azimuth at rise in degrees, clockwise from north
+    pub fn rise_azimuth(&self) -> f64 {
+        self.rise_azimuth
+    }
+
+    /// Azimuth at set in degrees, clockwise from north
+    pub fn set_azimuth(&self) -> f64 {
+        self.set_azimuth
+    }
+
+    /// Whether the pass can be seen with the naked eye
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    /// Shorthand for `visibility() == Visibility::Visible`
+    pub fn is_visible(&self) -> bool {
+        self.visibility == Visibility::Visible
+    }
+
+    /// Converts the pass into an `IssPassTime` as the api
+    /// would report it.
+    pub fn to_iss_pass_time(&self) -> IssPassTime {
+        IssPassTime {
+            risetime: self.rise,
+            duration: self.duration(),
+        }
+    }
+}
+
+/// Predicts passes of a satellite over an observer.
+pub struct PassPredictor<'a> {
+    sgp4: &'a Sgp4,
+    observer: Observer,
+    min_elevation: f64,
+}
+
+impl<'a> PassPredictor<'a> {
+    /// Predictor counting the time the satellite is at least
+    /// 10 degrees above the horizon, like open-notify does.
+    pub fn new(sgp4: &'a Sgp4, observer: Observer) -> PassPredictor<'a> {
+        PassPredictor {
+            sgp4,
+            observer,
+            min_elevation: 10.0,
+        }
+    }
+
+    /// Sets the elevation in degrees the satellite has to reach
+    /// for a pass to start.
+    pub fn min_elevation(mut self, min_elevation: f64) -> PassPredictor<'a> {
+        self.min_elevation = min_elevation;
+        self
+    }
+
+    /// Observer the passes are predicted for.
+    pub fn observer(&self) -> &Observer {
+        &self.observer
+    }
+
+    /// Passes rising between the unix timestamps `start` and `end`.
+    ///
+    /// A pass in progress at `start` is skipped, a pass rising
+    /// before `end` is reported completely.
+    pub fn passes(
+        &self,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<PredictedPass>, OpenNotificationError> {
+        let mut passes = Vec::new();
+        let mut t = start;
+        let mut above = self.elevation(t)? >= self.min_elevation;
+
+        while t < end {
+            let next = t + SEARCH_STEP;
+            let next_above = self.elevation(next)? >= self.min_elevation;
+            if !above && next_above {
+                let rise = self.crossing(t, next)?;
+                let pass = self.pass_from(rise)?;
+                t = pass.set;
+                above = false;
+                passes.push(pass);
+                continue;
+            }
+            t = next;
+            above = next_above;
+        }
+
+        passes.retain(|pass| pass.rise >= start && pass.rise < end);
+        Ok(passes)
+    }
+
+    /// The next `count` passes after the unix timestamp `start`.
+    ///
+    /// Gives up after searching 30 days, so fewer passes are returned
+    /// for observers the satellite never rises over.
+    pub fn next_passes(
+        &self,
+        start: i64,
+        count: usize,
+    ) -> Result<Vec<PredictedPass>, OpenNotificationError> {
+        let mut passes = Vec::new();
+        let mut window = start;
+
+        while passes.len() < count && window < start + MAX_SEARCH {
+            let mut found = self.passes(window, window + 86400)?;
+            if let Some(last) = found.last() {
+                window = last.set + 1;
+            } else {
+                window += 86400;
+            }
+            passes.append(&mut found);
+        }
+
+        passes.truncate(count);
+        Ok(passes)
+    }
+
+    /// Passes rising between `start` and `end` that can be seen
+    /// with the naked eye.
+    pub fn visible_passes(
+        &self,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<PredictedPass>, OpenNotificationError> {
+        let mut passes = self.passes(start, end)?;
+        passes.retain(PredictedPass::is_visible);
+        Ok(passes)
+    }
+
+    /// Classifies a pass reported by open-notify. `visible` filters
+    /// the passes of a whole reply.
+    ///
+    /// # Example
+    /// ```
+    /// use open_notify_api::orbit::{Sgp4, Tle};
+    /// use open_notify_api::predict::{Observer, PassPredictor, Visibility};
+    ///
+    /// let tle = Tle::parse(
+    ///     "1 25544U 98067A   18084.88124977  .00001628  00000-0  31711-4 0  9991",
+    ///     "2 25544  51.6416 359.2614 0001944 125.2585 357.1463 15.54190618105837",
+    /// ).unwrap();
+    /// let sgp4 = Sgp4::new(&tle).unwrap();
+    /// let observer = Observer::new(52.5, 13.4, 34.0).unwrap();
+    /// let predictor = PassPredictor::new(&sgp4, observer);
+    ///
+    /// let reply = open_notify_api::iss_pass_times_from_json(
+    ///     r#"{"message": "success",
+    ///     "request": {"altitude": 34, "datetime": 1522012140, "latitude": 52.5, "longitude": 13.4, "passes": 2},
+    ///     "response": [{"duration": 496, "risetime": 1522015000}, {"duration": 633, "risetime": 1522020700}]}"#,
+    /// ).unwrap();
+    /// for pass in reply.passes() {
+    ///     if predictor.visibility(pass).unwrap() == Visibility::Visible {
+    ///         println!("Look up at {}", pass.rise());
+    ///     }
+    /// }
+    /// ```
+    pub fn visibility(&self, pass: &IssPassTime) -> Result<Visibility, OpenNotificationError> {
+        self.classify(pass.rise(), pass.set_time())
+    }
+
+    /// Passes of an open-notify reply that can be seen with the naked
+    /// eye, in the order of the reply.
+    pub fn visible<'p>(
+        &self,
+        passes: &'p IssPassTimes,
+    ) -> Result<Vec<&'p IssPassTime>, OpenNotificationError> {
+        let mut visible = Vec::new();
+        for pass in passes.passes() {
+            if self.visibility(pass)? == Visibility::Visible {
+                visible.push(pass);
+            }
+        }
+        Ok(visible)
+    }
+
+    fn classify(&self, rise: i64, set: i64) -> Result<Visibility, OpenNotificationError> {
+        let mut visibility = Visibility::Daylight;
+        let mut t = rise;
+
+        while t <= set {
+            let sun = sun_position(t as f64);
+            let sun_elevation = self
+                .observer
+                .look_angles(&teme_to_ecef(&sun, t as f64))
+                .elevation;
+
+            if Daylight::from_elevation(sun_elevation) != Daylight::Day {
+                if is_sunlit(&self.sgp4.state_at(t as f64)?.position, &sun) {
+                    return Ok(Visibility::Visible);
+                }
+                visibility = Visibility::Eclipsed;
+            }
+
+            t = if t < set {
+                (t + VISIBILITY_STEP).min(set)
+            } else {
+                set + 1
+            };
+        }
+
+        Ok(visibility)
+    }
+
+    fn pass_from(&self, rise: i64) -> Result<PredictedPass, OpenNotificationError> {
+        let mut t = rise;
+        while self.elevation(t + SEARCH_STEP)? >= self.min_elevation {
+            t += SEARCH_STEP;
+        }
+        let set = self.crossing(t, t + SEARCH_STEP)?;
+
+        // The elevation has a single maximum during a pass.
+        let (mut low, mut high) = (rise, set);
+        while high - low > 2 {
+            let m1 = low + (high - low) / 3;
+            let m2 = high - (high - low) / 3;
+            if self.elevation(m1)? < self.elevation(m2)? {
+                low = m1;
+            } else {
+                high = m2;
+            }
+        }
+        let culmination = (low..=high)
+            .max_by(|&a, &b| {
+                let a = self.elevation(a).unwrap_or(-90.0);
+                let b = self.elevation(b).unwrap_or(-90.0);
+                a.partial_cmp(&b).unwrap()
+            })
+            .unwrap_or(low);
+
+        Ok(PredictedPass {
+            rise,
+            set,
+            culmination,
+            max_elevation: self.elevation(culmination)?,
+            rise_azimuth: self.look_angles(rise)?.azimuth,
+            set_azimuth: self.look_angles(set)?.azimuth,
+            visibility: self.classify(rise, set)?,
+        })
+    }
+
+    /// Finds the second the elevation crosses the minimum elevation
+    /// between `from` and `to`.
+    fn crossing(&self, mut from: i64, mut to: i64) -> Result<i64, OpenNotificationError> {
+        let rising = self.elevation(from)? < self.min_elevation;
+        while to - from > 1 {
+            let mid = from + (to - from) / 2;
+            if (self.elevation(mid)? < self.min_elevation) == rising {
+                from = mid;
+            } else {
+                to = mid;
+            }
+        }
+        Ok(if rising { to } else { from })
+    }
+
+    fn elevation(&self, timestamp: i64) -> Result<f64, OpenNotificationError> {
+        Ok(self.look_angles(timestamp)?.elevation)
+    }
+
+    fn look_angles(&self, timestamp: i64) -> Result<LookAngles, OpenNotificationError> {
+        let (_, ecef) = self.sgp4.position_at(timestamp as f64)?;
+        Ok(self.observer.look_angles(&ecef))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iss_pass_times_from_json;
+    use orbit::tests::iss_sgp4;
+
+    const START: i64 = 1522012140;
+
+    #[test]
+    fn observer_out_of_range() {
+        assert!(Observer::new(90.5, 13.4, 0.0).is_err());
+        assert!(Observer::new(52.5, -180.5, 0.0).is_err());
+        assert!(Observer::new(52.5, 13.4, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn look_angles_zenith() {
+        let observer = Observer::new(52.5, 13.4, 0.0).unwrap();
+        let target = geodetic_to_ecef(52.5, 13.4, 400.0);
+
+        let angles = observer.look_angles(&target);
+        assert!((angles.elevation - 90.0).abs() < 1e-6);
+        assert!((angles.range - 400.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn predicted_passes_are_consistent() {
+        let sgp4 = iss_sgp4();
+        let observer = Observer::new(52.5, 13.4, 34.0).unwrap();
+        let predictor = PassPredictor::new(&sgp4, observer);
+
+        let passes = predictor.passes(START, START + 2 * 86400).unwrap();
+        assert!(!passes.is_empty());
+
+        for pass in passes.iter() {
+            assert!(pass.rise() >= START && pass.rise() < START + 2 * 86400);
+            assert!(pass.duration() > 0 && pass.duration() < 15 * 60);
+            assert!(pass.culmination() >= pass.rise() && pass.culmination() <= pass.set_time());
+            assert!(pass.max_elevation() >= 10.0 && pass.max_elevation() <= 90.0);
+
+            assert!(predictor.elevation(pass.rise()).unwrap() >= 10.0);
+            assert!(predictor.elevation(pass.rise() - 1).unwrap() < 10.0);
+            assert!(predictor.elevation(pass.set_time()).unwrap() >= 10.0);
+            assert!(predictor.elevation(pass.set_time() + 1).unwrap() < 10.0);
+
+            let iss_pass_time = pass.to_iss_pass_time();
+            assert_eq!(iss_pass_time.rise(), pass.rise());
+            assert_eq!(iss_pass_time.duration(), pass.duration());
+        }
+
+        for pair in passes.windows(2) {
+            assert!(pair[0].set_time() < pair[1].rise());
+        }
+    }
+
+    #[test]
+    fn next_passes_count() {
+        let sgp4 = iss_sgp4();
+        let observer = Observer::new(52.5, 13.4, 34.0).unwrap();
+
+        let passes = PassPredictor::new(&sgp4, observer)
+            .next_passes(START, 7)
+            .unwrap();
+        assert_eq!(passes.len(), 7);
+    }
+
+    #[test]
+    fn next_passes_never_visible() {
+        let sgp4 = iss_sgp4();
+        let observer = Observer::new(89.0, 0.0, 0.0).unwrap();
+
+        let passes = PassPredictor::new(&sgp4, observer)
+            .next_passes(START, 1)
+            .unwrap();
+        assert!(passes.is_empty());
+    }
+
+    #[test]
+    fn visible_passes_are_visible() {
+        let sgp4 = iss_sgp4();
+        let observer = Observer::new(52.5, 13.4, 34.0).unwrap();
+        let predictor = PassPredictor::new(&sgp4, observer);
+
+        let passes = predictor.passes(START, START + 3 * 86400).unwrap();
+        let visible = predictor.visible_passes(START, START + 3 * 86400).unwrap();
+        assert!(visible.len() <= passes.len());
+
+        for pass in passes.iter() {
+            let observer_in_daylight = (pass.rise()..pass.set_time() + 1)
+                .all(|t| Daylight::at(&observer, t) == Daylight::Day);
+            match pass.visibility() {
+                Visibility::Daylight => assert!(observer_in_daylight),
+                Visibility::Visible | Visibility::Eclipsed => assert!(!observer_in_daylight),
+            }
+            assert_eq!(
+                predictor.visibility(&pass.to_iss_pass_time()).unwrap(),
+                pass.visibility()
+            );
+        }
+
+        for pass in visible.iter() {
+            assert!(pass.is_visible());
+            assert!(passes.contains(pass));
+        }
+    }
+
+    #[test]
+    fn visible_reply_passes() {
+        let sgp4 = iss_sgp4();
+        let predictor = PassPredictor::new(&sgp4, Observer::new(52.5, 13.4, 34.0).unwrap());
+        let reply = iss_pass_times_from_json(
+            r#"{"message": "success",
+            "request": {"altitude": 34, "datetime": 1521979200, "latitude": 52.5, "longitude": 13.4, "passes": 3},
+            "response": [{"duration": 500, "risetime": 1521979200}, {"duration": 496, "risetime": 1522015000},
+                         {"duration": 633, "risetime": 1522020700}]}"#,
+        )
+        .unwrap();
+
+        // The noon pass is in daylight.
+        assert_eq!(
+            predictor.visibility(&reply.passes()[0]).unwrap(),
+            Visibility::Daylight
+        );
+        let visible: Vec<i64> = predictor
+            .visible(&reply)
+            .unwrap()
+            .iter()
+            .map(|pass| pass.rise())
+            .collect();
+        assert_eq!(visible, vec![1522015000, 1522020700]);
+    }
+}
@@ -0,0 +1,215 @@
+//! Validated parameters of an ISS pass times request.
+
+use error::OpenNotificationError;
+
+/// Parameters of an `iss-pass.json` request.
+///
+/// Created through `IssPassRequest::builder`, which checks every
+/// parameter against the ranges accepted by open-notify.
+///
+/// # Example
+/// ```
+/// use open_notify_api::IssPassRequest;
+///
+/// let request = IssPassRequest::builder(52.5, 13.4)
+///     .altitude(10.0)
+///     .passes(3)
+///     .datetime(1521971230)
+///     .build()
+///     .unwrap();
+/// assert_eq!(request.passes(), 3);
+///
+/// assert!(IssPassRequest::builder(85.0, 13.4).build().is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct IssPassRequest {
+    latitude: f32,
+    longitude: f32,
+    altitude: f32,
+    passes: u32,
+    datetime: Option<i64>,
+}
+
+impl IssPassRequest {
+    /// Returns a builder for a request over the given location,
+    /// see `IssPassRequestBuilder` for the valid ranges.
+    pub fn builder(latitude: f32, longitude: f32) -> IssPassRequestBuilder {
+        IssPassRequestBuilder::new(latitude, longitude)
+    }
+
+    /// Latitude in degrees
+    pub fn latitude(&self) -> f32 {
+        self.latitude
+    }
+
+    /// Longitude in degrees
+    pub fn longitude(&self) -> f32 {
+        self.longitude
+    }
+
+    /// Altitude in meters
+    pub fn altitude(&self) -> f32 {
+        self.altitude
+    }
+
+    /// Number of requested passes
+    pub fn passes(&self) -> u32 {
+        self.passes
+    }
+
+    /// Unix timestamp to start the search for passes at,
+    /// `None` for the current time.
+    pub fn datetime(&self) -> Option<i64> {
+        self.datetime
+    }
+
+    pub(crate) fn query(&self) -> String {
+        let mut query = format!(
+            "lat={}&lon={}&alt={}&n={}",
+            self.latitude, self.longitude, self.altitude, self.passes,
+        );
+        if let Some(datetime) = self.datetime {
+            query.push_str(&format!("&datetime={}", datetime));
+        }
+        query
+    }
+}
+
+/// Builder for `IssPassRequest`.
+///
+/// # Parameters
+/// * `latitude` -80 to 80 in degrees
+/// * `longitude` -180 to 180 in degrees
+/// * `altitude` 0 to 10000 in meters; defaults to 100
+/// * `passes` 1 to 100; defaults to 5
+/// * `datetime` unix timestamp >= 0; defaults to the current time
+#[derive(Clone)]
+pub struct IssPassRequestBuilder {
+    latitude: f32,
+    longitude: f32,
+    altitude: f32,
+    passes: u32,
+    datetime: Option<i64>,
+}
+
+impl IssPassRequestBuilder {
+    pub fn new(latitude: f32, longitude: f32) -> IssPassRequestBuilder {
+        IssPassRequestBuilder {
+            latitude,
+            longitude,
+            altitude: 100.0,
+            passes: 5,
+            datetime: None,
+        }
+    }
+
+    pub fn altitude(mut self, altitude: f32) -> IssPassRequestBuilder {
+        self.altitude = altitude;
+        self
+    }
+
+    pub fn passes(mut self, passes: u32) -> IssPassRequestBuilder {
+        self.passes = passes;
+        self
+    }
+
+    /// Unix timestamp to start the search for passes at.
+    pub fn datetime(mut self, datetime: i64) -> IssPassRequestBuilder {
+        self.datetime = Some(datetime);
+        self
+    }
+
+    /// Validates the parameters.
+    ///
+    /// Returns `OpenNotificationError::InvalidArgument` naming the
+    /// first parameter that is out of range.
+    pub fn build(self) -> Result<IssPassRequest, OpenNotificationError> {
+        check_range("latitude", self.latitude, -80.0, 80.0)?;
+        check_range("longitude", self.longitude, -180.0, 180.0)?;
+        check_range("altitude", self.altitude, 0.0, 10000.0)?;
+
+        if self.passes < 1 || self.passes > 100 {
+            return Err(OpenNotificationError::InvalidArgument(format!(
+                "passes {} is out of range 1 to 100",
+                self.passes
+            )));
+        }
+
+        if let Some(datetime) = self.datetime {
+            if datetime < 0 {
+                return Err(OpenNotificationError::InvalidArgument(format!(
+                    "datetime {} is before the unix epoch",
+                    datetime
+                )));
+            }
+        }
+
+        Ok(IssPassRequest {
+            latitude: self.latitude,
+            longitude: self.longitude,
+            altitude: self.altitude,
+            passes: self.passes,
+            datetime: self.datetime,
+        })
+    }
+}
+
+fn check_range(name: &str, value: f32, min: f32, max: f32) -> Result<(), OpenNotificationError> {
+    if value >= min && value <= max {
+        Ok(())
+    } else {
+        Err(OpenNotificationError::InvalidArgument(format!(
+            "{} {} is out of range {} to {}",
+            name, value, min, max
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_defaults() {
+        let request = IssPassRequest::builder(52.5, 13.4).build().unwrap();
+        assert_eq!(request.altitude(), 100.0);
+        assert_eq!(request.passes(), 5);
+        assert_eq!(request.datetime(), None);
+        assert_eq!(request.query(), "lat=52.5&lon=13.4&alt=100&n=5");
+    }
+
+    #[test]
+    fn request_with_datetime() {
+        let request = IssPassRequest::builder(52.5, 13.4)
+            .altitude(10.0)
+            .passes(1)
+            .datetime(1521971230)
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.query(),
+            "lat=52.5&lon=13.4&alt=10&n=1&datetime=1521971230"
+        );
+    }
+
+    #[test]
+    fn request_out_of_range() {
+        let invalid = vec![
+            IssPassRequest::builder(-80.5, 13.4),
+            IssPassRequest::builder(52.5, 180.5),
+            IssPassRequest::builder(f32::NAN, 13.4),
+            IssPassRequest::builder(52.5, 13.4).altitude(-1.0),
+            IssPassRequest::builder(52.5, 13.4).altitude(10000.5),
+            IssPassRequest::builder(52.5, 13.4).passes(0),
+            IssPassRequest::builder(52.5, 13.4).passes(101),
+            IssPassRequest::builder(52.5, 13.4).datetime(-1),
+        ];
+
+        for builder in invalid {
+            match builder.build() {
+                Err(OpenNotificationError::InvalidArgument(_)) => {}
+                _ => panic!(),
+            }
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! Retrying failed requests and failing fast while open-notify is down.
+//!
+//! A `RetryPolicy` repeats requests failing with a transient error,
+//! see `OpenNotificationError::is_transient`, waiting an exponentially
+//! growing and optionally jittered delay in between.
+//!
+//! A `CircuitBreaker` counts consecutive transient failures. Once a
+//! threshold is reached it opens and requests fail immediately with
+//! `OpenNotificationError::CircuitOpen`. After a cool down a single
+//! trial request is let through, closing the breaker on success.
+//!
+//! Both are configured on `OpenNotifyClientBuilder`. Retries only
+//! apply to the blocking `OpenNotifyClient`, the circuit breaker also
+//! to `AsyncOpenNotifyClient`.
+//!
+//! # Example
+//! ```
+//! use std::time::Duration;
+//! use open_notify_api::retry::{CircuitBreaker, RetryPolicy};
+//! use open_notify_api::transport::MemoryTransport;
+//! use open_notify_api::OpenNotifyClientBuilder;
+//!
+//! let client = OpenNotifyClientBuilder::new()
+//!     .retry(RetryPolicy::new(4).initial_backoff(Duration::from_millis(500)))
+//!     .circuit_breaker(CircuitBreaker::new(5, Duration::from_secs(60)))
+//!     .build_with_transport(MemoryTransport::new());
+//! assert!(!client.circuit_breaker().unwrap().is_open());
+//! ```
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How often and how patiently failed requests are repeated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+}
+
+impl RetryPolicy {
+    /// Tries each request up to `max_attempts` times, starting with
+    /// a delay of 200ms that doubles up to 5s, with jitter.
+    pub fn new(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+        }
+    }
+
+    /// Delay before the first retry.
+    pub fn initial_backoff(mut self, backoff: Duration) -> RetryPolicy {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Upper bound of the delay between two attempts.
+    pub fn max_backoff(mut self, backoff: Duration) -> RetryPolicy {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Randomizes each delay to between half and all of its nominal
+    /// value, so clients failing together don't retry together.
+    pub fn jitter(mut self, jitter: bool) -> RetryPolicy {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Maximal number of attempts per request, including the first.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Nominal delay before retry number `retry`, counting from 1.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(retry.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+
+    /// Delay to actually wait before retry number `retry`.
+    pub(crate) fn delay(&self, retry: u32) -> Duration {
+        let backoff = self.backoff(retry);
+        if self.jitter {
+            let half = backoff / 2;
+            let spread = (backoff - half).as_nanos() as u64;
+            half + Duration::from_nanos(random() % (spread + 1))
+        } else {
+            backoff
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy::new(3)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Closed(u32),
+    Open(Instant),
+    HalfOpen,
+}
+
+/// Stops sending requests while open-notify keeps failing.
+///
+/// Clones of a client share their breaker.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cool_down: Duration,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    /// Opens after `threshold` consecutive transient failures and
+    /// stays open for `cool_down`.
+    pub fn new(threshold: u32, cool_down: Duration) -> CircuitBreaker {
+        CircuitBreaker {
+            threshold: threshold.max(1),
+            cool_down,
+            state: Mutex::new(State::Closed(0)),
+        }
+    }
+
+    /// Whether requests are currently rejected.
+    pub fn is_open(&self) -> bool {
+        match *self.state.lock().unwrap() {
+            State::Closed(_) => false,
+            State::Open(until) => Instant::now() < until,
+            State::HalfOpen => true,
+        }
+    }
+
+    /// Whether a request may be sent. Moves an open breaker whose
+    /// cool down has passed to half open, admitting one trial.
+    pub(crate) fn allow(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            State::Closed(_) => true,
+            State::Open(until) if Instant::now() >= until => {
+                *state = State::HalfOpen;
+                true
+            }
+            State::Open(_) | State::HalfOpen => false,
+        }
+    }
+
+    pub(crate) fn record_success(&self) {
+        *self.state.lock().unwrap() = State::Closed(0);
+    }
+
+    /// Gives up a request admitted by `allow` without an outcome, e.g.
+    /// a dropped future. An abandoned trial lets the next request be
+    /// the trial.
+    #[cfg(feature = "async")]
+    pub(crate) fn record_abandoned(&self) {
+        let mut state = self.state.lock().unwrap();
+        if *state == State::HalfOpen {
+            *state = State::Open(Instant::now());
+        }
+    }
+
+    pub(crate) fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        *state = match *state {
+            State::Closed(failures) if failures + 1 < self.threshold => State::Closed(failures + 1),
+            State::Open(until) => State::Open(until),
+            _ => State::Open(Instant::now() + self.cool_down),
+        };
+    }
+}
+
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn exponential_backoff() {
+        let policy = RetryPolicy::new(10)
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_secs(1));
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_secs(1));
+        assert_eq!(policy.backoff(64), Duration::from_secs(1));
+
+        for retry in 1..6 {
+            let delay = policy.delay(retry);
+            assert!(delay >= policy.backoff(retry) / 2);
+            assert!(delay <= policy.backoff(retry));
+        }
+        assert_eq!(policy.jitter(false).delay(3), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn breaker_opens_and_recovers() {
+        let breaker = CircuitBreaker::new(2, Duration::from_millis(20));
+
+        assert!(breaker.allow());
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        assert!(!breaker.allow());
+
+        thread::sleep(Duration::from_millis(30));
+        assert!(breaker.allow());
+        assert!(!breaker.allow());
+        breaker.record_failure();
+        assert!(breaker.is_open());
+
+        thread::sleep(Duration::from_millis(30));
+        assert!(breaker.allow());
+        breaker.record_success();
+        assert!(!breaker.is_open());
+        assert!(breaker.allow());
+    }
+}
@@ -0,0 +1,338 @@
+//! Changes of the crew in space between `Astros` snapshots.
+//!
+//! `Astros::diff` compares two snapshots. A `RosterTracker` follows
+//! the snapshots over time, remembering when each person was first
+//! and last seen, and can be saved to and loaded from a JSON file to
+//! keep counting across restarts.
+//!
+//! open-notify doesn't report launch dates, so the time in space is
+//! counted from the first snapshot listing a person.
+//!
+//! # Example
+//! ```no_run
+//! use open_notify_api::roster::RosterTracker;
+//!
+//! let astros = open_notify_api::astro_from_json(r#"{"message": "success", "number": 2, "people": [
+//!     {"name": "Anton Shkaplerov", "craft": "ISS"},
+//!     {"name": "Scott Tingle", "craft": "ISS"}]}"#).unwrap();
+//! let mut tracker = RosterTracker::load("roster.json").unwrap();
+//! let now = 1522012140;
+//! let diff = tracker.update(&astros, now);
+//! for person in diff.arrivals() {
+//!     println!("{} arrived at {}", person.name(), person.craft());
+//! }
+//! for entry in tracker.current() {
+//!     println!("{}: {:.0} days", entry.name(), entry.days_in_space(now));
+//! }
+//! tracker.save("roster.json").unwrap();
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use craft::Craft;
+use {Astros, Person};
+
+/// A person moving from one craft to another.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transfer {
+    name: String,
+    from: String,
+    to: String,
+}
+
+impl Transfer {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Craft the person left
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    /// Craft the person is on now
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+}
+
+/// Difference between two rosters.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RosterDiff {
+    arrivals: Vec<Person>,
+    departures: Vec<Person>,
+    transfers: Vec<Transfer>,
+}
+
+impl RosterDiff {
+    /// People only in the newer roster, with their craft.
+    pub fn arrivals(&self) -> &[Person] {
+        &self.arrivals
+    }
+
+    /// People only in the older roster, with their last craft.
+    pub fn departures(&self) -> &[Person] {
+        &self.departures
+    }
+
+    /// People in both rosters on different crafts.
+    pub fn transfers(&self) -> &[Transfer] {
+        &self.transfers
+    }
+
+    /// Whether both rosters list the same people on the same crafts.
+    pub fn is_empty(&self) -> bool {
+        self.arrivals.is_empty() && self.departures.is_empty() && self.transfers.is_empty()
+    }
+}
+
+impl Astros {
+    /// Changes from this roster to the `newer` one. People are matched
+    /// by name, crafts by normalized name.
+    pub fn diff(&self, newer: &Astros) -> RosterDiff {
+        diff(self.people(), newer.people())
+    }
+}
+
+fn diff(older: &[Person], newer: &[Person]) -> RosterDiff {
+    let before: HashMap<&str, &str> = older.iter().map(|p| (p.name(), p.craft())).collect();
+    let after: HashMap<&str, &str> = newer.iter().map(|p| (p.name(), p.craft())).collect();
+
+    let mut result = RosterDiff::default();
+    for person in newer {
+        match before.get(person.name()) {
+            None => result.arrivals.push(person.clone()),
+            Some(&craft) if Craft::new(craft) != person.craft_normalized() => {
+                result.transfers.push(Transfer {
+                    name: String::from(person.name()),
+                    from: String::from(craft),
+                    to: String::from(person.craft()),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    result.departures = older
+        .iter()
+        .filter(|p| !after.contains_key(p.name()))
+        .cloned()
+        .collect();
+    result
+}
+
+/// What a `RosterTracker` knows about a person.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct RosterEntry {
+    name: String,
+    craft: String,
+    first_seen: i64,
+    last_seen: i64,
+    in_space: bool,
+}
+
+impl RosterEntry {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Craft the person was last seen on
+    pub fn craft(&self) -> &str {
+        &self.craft
+    }
+
+    /// Unix timestamp of the first snapshot of the current or, after
+    /// a departure, of the last stay in space listing the person.
+    pub fn first_seen(&self) -> i64 {
+        self.first_seen
+    }
+
+    /// Unix timestamp of the last snapshot listing the person.
+    pub fn last_seen(&self) -> i64 {
+        self.last_seen
+    }
+
+    /// Whether the latest snapshot lists the person.
+    pub fn is_in_space(&self) -> bool {
+        self.in_space
+    }
+
+    /// Days between the first sighting and `now` while in space, or
+    /// the last sighting after a departure.
+    pub fn days_in_space(&self, now: i64) -> f64 {
+        let end = if self.in_space { now } else { self.last_seen };
+        (end - self.first_seen).max(0) as f64 / 86400.0
+    }
+}
+
+/// Follows `Astros` snapshots over time.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct RosterTracker {
+    entries: Vec<RosterEntry>,
+}
+
+impl RosterTracker {
+    pub fn new() -> RosterTracker {
+        RosterTracker::default()
+    }
+
+    /// Tracker saved at `path`, or a new one if there is no such file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<RosterTracker> {
+        match fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => Ok(RosterTracker::new()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Saves the tracker as JSON to `path`, replacing the file
+    /// atomically.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        let data = serde_json::to_string(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Records the snapshot `astros` taken at the unix `timestamp` and
+    /// returns the changes since the previous snapshot.
+    pub fn update(&mut self, astros: &Astros, timestamp: i64) -> RosterDiff {
+        let current: Vec<Person> = self
+            .current()
+            .map(|entry| Person::new(&entry.name, &entry.craft))
+            .collect();
+        let changes = diff(&current, astros.people());
+
+        for entry in &mut self.entries {
+            entry.in_space = false;
+        }
+        for person in astros.people() {
+            match self.entries.iter_mut().find(|e| e.name == person.name()) {
+                Some(entry) => {
+                    if current.iter().all(|p| p.name() != person.name()) {
+                        entry.first_seen = timestamp;
+                    }
+                    entry.craft = String::from(person.craft());
+                    entry.last_seen = timestamp;
+                    entry.in_space = true;
+                }
+                None => self.entries.push(RosterEntry {
+                    name: String::from(person.name()),
+                    craft: String::from(person.craft()),
+                    first_seen: timestamp,
+                    last_seen: timestamp,
+                    in_space: true,
+                }),
+            }
+        }
+        changes
+    }
+
+    /// Everyone ever seen, in order of their first sighting.
+    pub fn entries(&self) -> &[RosterEntry] {
+        &self.entries
+    }
+
+    /// People listed in the latest snapshot.
+    pub fn current(&self) -> impl Iterator<Item = &RosterEntry> {
+        self.entries.iter().filter(|entry| entry.in_space)
+    }
+
+    pub fn entry(&self, name: &str) -> Option<&RosterEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tests::{astros, temp_path};
+
+    #[test]
+    fn diff_snapshots() {
+        let older = astros(&[
+            ("Anton Shkaplerov", "ISS"),
+            ("Scott Tingle", "ISS"),
+            ("Norishige Kanai", "ISS"),
+        ]);
+        let newer = astros(&[
+            ("Anton Shkaplerov", "Soyuz MS-07"),
+            ("Scott Tingle", "ISS"),
+            ("Oleg Artemyev", "ISS"),
+        ]);
+
+        let diff = older.diff(&newer);
+        assert_eq!(diff.arrivals(), &[Person::new("Oleg Artemyev", "ISS")]);
+        assert_eq!(diff.departures(), &[Person::new("Norishige Kanai", "ISS")]);
+        assert_eq!(diff.transfers().len(), 1);
+        let transfer = &diff.transfers()[0];
+        assert_eq!(transfer.name(), "Anton Shkaplerov");
+        assert_eq!(transfer.from(), "ISS");
+        assert_eq!(transfer.to(), "Soyuz MS-07");
+
+        assert!(newer.diff(&newer).is_empty());
+        assert!(newer
+            .diff(&astros(&[
+                ("Anton Shkaplerov", "soyuz ms 7"),
+                ("Scott Tingle", "International Space Station"),
+                ("Oleg Artemyev", "iss"),
+            ]))
+            .is_empty());
+    }
+
+    #[test]
+    fn tracker_counts_days() {
+        let day = 86400;
+        let mut tracker = RosterTracker::new();
+
+        let diff = tracker.update(&astros(&[("A", "ISS"), ("B", "ISS")]), 0);
+        assert_eq!(diff.arrivals().len(), 2);
+
+        let diff = tracker.update(&astros(&[("A", "ISS")]), 10 * day);
+        assert_eq!(diff.departures(), &[Person::new("B", "ISS")]);
+        assert!(tracker
+            .update(&astros(&[("A", "ISS")]), 20 * day)
+            .is_empty());
+
+        let a = tracker.entry("A").unwrap();
+        assert!(a.is_in_space());
+        assert_eq!(a.days_in_space(25 * day), 25.0);
+        let b = tracker.entry("B").unwrap();
+        assert!(!b.is_in_space());
+        assert_eq!(b.last_seen(), 0);
+        assert_eq!(b.days_in_space(25 * day), 0.0);
+        assert_eq!(tracker.current().count(), 1);
+
+        // A new stay starts counting again.
+        let diff = tracker.update(&astros(&[("A", "ISS"), ("B", "Tiangong")]), 30 * day);
+        assert_eq!(diff.arrivals(), &[Person::new("B", "Tiangong")]);
+        let b = tracker.entry("B").unwrap();
+        assert_eq!(b.first_seen(), 30 * day);
+        assert_eq!(b.craft(), "Tiangong");
+        assert_eq!(tracker.entries().len(), 2);
+    }
+
+    #[test]
+    fn tracker_persists() {
+        let path = temp_path("roster.json");
+        let _ = fs::remove_file(&path);
+
+        let mut tracker = RosterTracker::load(&path).unwrap();
+        assert!(tracker.entries().is_empty());
+        tracker.update(&astros(&[("A", "ISS")]), 100);
+        tracker.save(&path).unwrap();
+
+        let loaded = RosterTracker::load(&path).unwrap();
+        assert_eq!(loaded, tracker);
+        assert_eq!(loaded.entry("A").unwrap().first_seen(), 100);
+
+        fs::write(&path, "not json").unwrap();
+        assert!(RosterTracker::load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
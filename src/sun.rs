@@ -0,0 +1,158 @@
+//! Low precision sun position and twilight phases.
+//!
+//! The sun is placed with the algorithm of the Astronomical Almanac,
+//! accurate to about 0.01 degrees, which is plenty to tell whether
+//! the ISS or an observer is in sunlight.
+
+use std::f64::consts::PI;
+
+use orbit::teme_to_ecef;
+use predict::Observer;
+
+const ASTRONOMICAL_UNIT: f64 = 149_597_870.7;
+const EARTH_RADIUS: f64 = 6378.137;
+
+/// Elevation of the sun's center at sunrise and sunset in degrees,
+/// accounting for refraction and the apparent radius of the sun.
+const SUNSET_ELEVATION: f64 = -0.833;
+
+/// Phase of the day at an observer's location.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Daylight {
+    /// The sun is above the horizon.
+    Day,
+    /// The sun is up to 6 degrees below the horizon.
+    CivilTwilight,
+    /// The sun is 6 to 12 degrees below the horizon.
+    NauticalTwilight,
+    /// The sun is 12 to 18 degrees below the horizon.
+    AstronomicalTwilight,
+    /// The sun is more than 18 degrees below the horizon.
+    Night,
+}
+
+impl Daylight {
+    /// Phase of the day for `observer` at the unix `timestamp`.
+    pub fn at(observer: &Observer, timestamp: i64) -> Daylight {
+        Daylight::from_elevation(sun_elevation(observer, timestamp))
+    }
+
+    /// Phase of the day for a given elevation of the sun in degrees.
+    pub fn from_elevation(elevation: f64) -> Daylight {
+        if elevation >= SUNSET_ELEVATION {
+            Daylight::Day
+        } else if elevation >= -6.0 {
+            Daylight::CivilTwilight
+        } else if elevation >= -12.0 {
+            Daylight::NauticalTwilight
+        } else if elevation >= -18.0 {
+            Daylight::AstronomicalTwilight
+        } else {
+            Daylight::Night
+        }
+    }
+}
+
+/// Elevation of the sun above the horizon of `observer` in degrees.
+pub fn sun_elevation(observer: &Observer, timestamp: i64) -> f64 {
+    let timestamp = timestamp as f64;
+    observer
+        .look_angles(&teme_to_ecef(&sun_position(timestamp), timestamp))
+        .elevation
+}
+
+/// Position of the sun in kilometers in the earth centered inertial
+/// frame of date at a fractional unix timestamp.
+pub(crate) fn sun_position(timestamp: f64) -> [f64; 3] {
+    let deg = PI / 180.0;
+    let n = timestamp / 86400.0 + 2440587.5 - 2451545.0;
+
+    let mean_longitude = 280.460 + 0.9856474 * n;
+    let mean_anomaly = (357.528 + 0.9856003 * n) * deg;
+    let ecliptic_longitude =
+        (mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2.0 * mean_anomaly).sin()) * deg;
+    let obliquity = (23.439 - 0.0000004 * n) * deg;
+    let distance = (1.00014 - 0.01671 * mean_anomaly.cos() - 0.00014 * (2.0 * mean_anomaly).cos())
+        * ASTRONOMICAL_UNIT;
+
+    [
+        distance * ecliptic_longitude.cos(),
+        distance * obliquity.cos() * ecliptic_longitude.sin(),
+        distance * obliquity.sin() * ecliptic_longitude.sin(),
+    ]
+}
+
+/// Whether a satellite at `position` (km, inertial frame) is lit by
+/// the sun at `sun`, using a cylindrical earth shadow.
+pub(crate) fn is_sunlit(position: &[f64; 3], sun: &[f64; 3]) -> bool {
+    let sun_distance = (sun[0] * sun[0] + sun[1] * sun[1] + sun[2] * sun[2]).sqrt();
+    let s = [
+        sun[0] / sun_distance,
+        sun[1] / sun_distance,
+        sun[2] / sun_distance,
+    ];
+
+    let along = position[0] * s[0] + position[1] * s[1] + position[2] * s[2];
+    if along >= 0.0 {
+        return true;
+    }
+
+    let perpendicular = [
+        position[0] - along * s[0],
+        position[1] - along * s[1],
+        position[2] - along * s[2],
+    ];
+    (perpendicular[0] * perpendicular[0]
+        + perpendicular[1] * perpendicular[1]
+        + perpendicular[2] * perpendicular[2])
+        .sqrt()
+        > EARTH_RADIUS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sun_at_equinox() {
+        // 2018-03-20T16:15:00Z
+        let sun = sun_position(1521562500.0);
+        let declination = (sun[2] / (sun[0] * sun[0] + sun[1] * sun[1] + sun[2] * sun[2]).sqrt())
+            .asin()
+            .to_degrees();
+        assert!(declination.abs() < 0.05);
+    }
+
+    #[test]
+    fn daylight_in_berlin() {
+        let berlin = Observer::new(52.5, 13.4, 34.0).unwrap();
+
+        // Solar noon on 2018-03-20 is about 11:14 UTC.
+        let noon = sun_elevation(&berlin, 1521544440);
+        assert!((noon - 37.5).abs() < 0.5);
+
+        assert_eq!(Daylight::at(&berlin, 1521544440), Daylight::Day);
+        assert_eq!(Daylight::at(&berlin, 1521504000), Daylight::Night);
+    }
+
+    #[test]
+    fn daylight_phases() {
+        assert_eq!(Daylight::from_elevation(-0.5), Daylight::Day);
+        assert_eq!(Daylight::from_elevation(-3.0), Daylight::CivilTwilight);
+        assert_eq!(Daylight::from_elevation(-9.0), Daylight::NauticalTwilight);
+        assert_eq!(
+            Daylight::from_elevation(-15.0),
+            Daylight::AstronomicalTwilight
+        );
+        assert_eq!(Daylight::from_elevation(-30.0), Daylight::Night);
+    }
+
+    #[test]
+    fn earth_shadow() {
+        let sun = [ASTRONOMICAL_UNIT, 0.0, 0.0];
+        assert!(is_sunlit(&[6778.0, 0.0, 0.0], &sun));
+        assert!(is_sunlit(&[0.0, 6778.0, 0.0], &sun));
+        assert!(!is_sunlit(&[-6778.0, 0.0, 0.0], &sun));
+        assert!(is_sunlit(&[-6778.0, 0.0, 6500.0], &sun));
+    }
+}
@@ -0,0 +1,124 @@
+//! Typed time accessors based on `chrono`.
+//!
+//! Only available with the `chrono` feature. Timestamps beyond the
+//! range `chrono` can represent saturate at its minimum or maximum.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+use {IssNow, IssPassTime, IssPassTimes};
+
+impl IssNow {
+    /// Time the position was captured at.
+    pub fn datetime(&self) -> DateTime<Utc> {
+        datetime_from_timestamp(self.timestamp)
+    }
+}
+
+impl IssPassTime {
+    /// Time the ISS rises above the horizon.
+    pub fn rise_datetime(&self) -> DateTime<Utc> {
+        datetime_from_timestamp(self.rise())
+    }
+
+    /// Time the ISS sets below the horizon.
+    pub fn set_datetime(&self) -> DateTime<Utc> {
+        datetime_from_timestamp(self.set_time())
+    }
+
+    /// How long the ISS is visible above the horizon.
+    pub fn pass_duration(&self) -> Duration {
+        Duration::try_seconds(self.duration).unwrap_or(if self.duration < 0 {
+            Duration::MIN
+        } else {
+            Duration::MAX
+        })
+    }
+
+    /// Rise and set time in the time zone `tz`.
+    ///
+    /// Works with `FixedOffset` as well as IANA time zones provided
+    /// by crates like `chrono-tz`.
+    ///
+    /// # Example
+    /// ```
+    /// # extern crate chrono;
+    /// # extern crate open_notify_api;
+    /// # fn main() {
+    /// use chrono::FixedOffset;
+    ///
+    /// let data = r#"{"message": "success",
+    ///     "response": [{"risetime": 1521985612, "duration": 496}]}"#;
+    /// let pass_times = open_notify_api::iss_pass_times_from_json(data).unwrap();
+    ///
+    /// let cest = FixedOffset::east_opt(2 * 3600).unwrap();
+    /// let (rise, set) = pass_times.passes()[0].in_timezone(&cest);
+    /// assert_eq!(rise.to_rfc3339(), "2018-03-25T15:46:52+02:00");
+    /// assert_eq!(set.to_rfc3339(), "2018-03-25T15:55:08+02:00");
+    /// # }
+    /// ```
+    pub fn in_timezone<Tz: TimeZone>(&self, tz: &Tz) -> (DateTime<Tz>, DateTime<Tz>) {
+        (
+            self.rise_datetime().with_timezone(tz),
+            self.set_datetime().with_timezone(tz),
+        )
+    }
+}
+
+impl IssPassTimes {
+    /// Time the search for passes started at, as echoed by the server.
+    pub fn start_datetime(&self) -> DateTime<Utc> {
+        datetime_from_timestamp(self.request_timestamp())
+    }
+}
+
+fn datetime_from_timestamp(timestamp: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(timestamp, 0).unwrap_or(if timestamp < 0 {
+        DateTime::<Utc>::MIN_UTC
+    } else {
+        DateTime::<Utc>::MAX_UTC
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+    use {iss_now_from_json, iss_pass_times_from_json};
+
+    #[test]
+    fn iss_now_datetime() {
+        let input_data = r#"{
+            "iss_position": {"longitude": 73.5964, "latitude": -34.6445},
+            "message": "success",
+            "timestamp": 1521971230}"#;
+        let iss_now = iss_now_from_json(input_data).unwrap();
+        assert_eq!(iss_now.datetime().to_rfc3339(), "2018-03-25T09:47:10+00:00");
+    }
+
+    #[test]
+    fn iss_pass_time_datetimes() {
+        let input_data = r#"{
+            "message": "success",
+            "response": [{"risetime": 1521985612, "duration": 496}]
+            }"#;
+        let pass_times = iss_pass_times_from_json(input_data).unwrap();
+        let pass = &pass_times.passes()[0];
+
+        assert_eq!(pass.set_time(), 1521986108);
+        assert_eq!(pass.pass_duration(), Duration::seconds(496));
+        assert_eq!(
+            pass.set_datetime() - pass.rise_datetime(),
+            pass.pass_duration()
+        );
+
+        let (rise, set) = pass.in_timezone(&FixedOffset::west_opt(5 * 3600).unwrap());
+        assert_eq!(rise.to_rfc3339(), "2018-03-25T08:46:52-05:00");
+        assert_eq!(set.to_rfc3339(), "2018-03-25T08:55:08-05:00");
+    }
+
+    #[test]
+    fn timestamp_out_of_range() {
+        assert_eq!(datetime_from_timestamp(i64::MAX), DateTime::<Utc>::MAX_UTC);
+        assert_eq!(datetime_from_timestamp(i64::MIN), DateTime::<Utc>::MIN_UTC);
+    }
+}
@@ -0,0 +1,526 @@
+//! Continuous tracking of the ISS position.
+//!
+//! `Tracker` polls `iss_now` at a fixed interval and yields the
+//! positions as an `Iterator`. With the `async` feature `AsyncTracker`
+//! does the same as a `Stream`.
+//!
+//! Samples repeating or preceding the timestamp of the previous one
+//! are dropped. A sample following the previous one by more than the
+//! configured gap carries the length of the gap, e.g. to break a line
+//! drawn on a map. Failed requests are yielded as errors and polling
+//! continues.
+//!
+//! Both end once `Shutdown::shutdown` is called on their handle, also
+//! when waiting for the next poll.
+//!
+//! # Example
+//! ```
+//! use std::time::Duration;
+//! use open_notify_api::track::Tracker;
+//! use open_notify_api::transport::MemoryTransport;
+//! use open_notify_api::OpenNotifyClient;
+//!
+//! let mut transport = MemoryTransport::new();
+//! transport.insert(
+//!     "http://api.open-notify.org/iss-now.json",
+//!     200,
+//!     r#"{"message": "success", "timestamp": 1521971230,
+//!         "iss_position": {"latitude": -34.6445, "longitude": 73.5964}}"#,
+//! );
+//! let client = OpenNotifyClient::with_transport(transport);
+//! let tracker = Tracker::new(&client, Duration::from_secs(5)).unwrap();
+//! let shutdown = tracker.shutdown_handle();
+//!
+//! for sample in tracker.take(1) {
+//!     match sample {
+//!         Ok(sample) => println!("{} {}", sample.iss_now().latitude(), sample.iss_now().longitude()),
+//!         Err(e) => eprintln!("{}", e),
+//!     }
+//! }
+//! shutdown.shutdown();
+//! ```
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "async")]
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+#[cfg(feature = "async")]
+use std::task::{Context, Poll, Waker};
+#[cfg(feature = "async")]
+use std::thread;
+
+#[cfg(feature = "async")]
+use futures::stream::Stream;
+
+#[cfg(feature = "async")]
+use async_client::{AsyncOpenNotifyClient, Fetch};
+use client::OpenNotifyClient;
+use error::OpenNotificationError;
+#[cfg(feature = "async")]
+use transport::AsyncTransport;
+use transport::Transport;
+use IssNow;
+
+/// Position of the ISS yielded by a tracker.
+pub struct Sample {
+    iss_now: IssNow,
+    gap: Option<Duration>,
+}
+
+impl Sample {
+    /// Position of the ISS.
+    pub fn iss_now(&self) -> &IssNow {
+        &self.iss_now
+    }
+
+    /// Consumes the sample, returning the position.
+    pub fn into_iss_now(self) -> IssNow {
+        self.iss_now
+    }
+
+    /// Time since the previous sample if it exceeds the maximal gap.
+    pub fn gap(&self) -> Option<Duration> {
+        self.gap
+    }
+}
+
+/// Handle to stop a tracker, possibly from another thread.
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    state: Arc<(Mutex<bool>, Condvar)>,
+    #[cfg(feature = "async")]
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Shutdown {
+        Shutdown::default()
+    }
+
+    /// Stops the trackers using this handle.
+    pub fn shutdown(&self) {
+        let (ref stopped, ref condvar) = *self.state;
+        *stopped.lock().unwrap() = true;
+        condvar.notify_all();
+        #[cfg(feature = "async")]
+        {
+            if let Some(waker) = self.waker.lock().unwrap().take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Whether `shutdown` has been called.
+    pub fn is_shutdown(&self) -> bool {
+        *self.state.0.lock().unwrap()
+    }
+
+    /// Blocks until `deadline` or a shutdown, whichever comes first.
+    /// Returns whether the handle has been shut down.
+    fn wait_until(&self, deadline: Instant) -> bool {
+        let (ref stopped, ref condvar) = *self.state;
+        let mut stopped = stopped.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            if *stopped || now >= deadline {
+                return *stopped;
+            }
+            stopped = condvar.wait_timeout(stopped, deadline - now).unwrap().0;
+        }
+    }
+
+    /// Wakes the task of `cx` on shutdown.
+    #[cfg(feature = "async")]
+    fn wake_on_shutdown(&self, cx: &Context) {
+        *self.waker.lock().unwrap() = Some(cx.waker().clone());
+    }
+}
+
+/// Polling schedule and deduplication shared by the trackers.
+struct Schedule {
+    interval: Duration,
+    max_gap: Duration,
+    next_poll: Option<Instant>,
+    last_timestamp: Option<i64>,
+}
+
+impl Schedule {
+    fn new(interval: Duration) -> Result<Schedule, OpenNotificationError> {
+        if interval == Duration::from_secs(0) {
+            return Err(OpenNotificationError::InvalidArgument(String::from(
+                "interval must not be zero",
+            )));
+        }
+        Ok(Schedule {
+            interval,
+            max_gap: interval * 2,
+            next_poll: None,
+            last_timestamp: None,
+        })
+    }
+
+    /// Time the next poll is due, `now` before the first poll.
+    fn due(&self, now: Instant) -> Instant {
+        self.next_poll.unwrap_or(now)
+    }
+
+    /// Records a poll started at `now`. Polls keep a fixed rate
+    /// unless they fall behind.
+    fn polled(&mut self, now: Instant) {
+        let next = self.due(now) + self.interval;
+        self.next_poll = Some(if next < now { now } else { next });
+    }
+
+    /// Turns a position into a sample, `None` for duplicates.
+    fn accept(&mut self, iss_now: IssNow) -> Option<Sample> {
+        let timestamp = iss_now.timestamp();
+        let gap = match self.last_timestamp {
+            Some(last) if timestamp <= last => return None,
+            Some(last) => Some(Duration::from_secs((timestamp - last) as u64))
+                .filter(|gap| *gap > self.max_gap),
+            None => None,
+        };
+        self.last_timestamp = Some(timestamp);
+        Some(Sample { iss_now, gap })
+    }
+}
+
+/// Polls the ISS position at a fixed interval.
+pub struct Tracker<'a, T: 'a> {
+    client: &'a OpenNotifyClient<T>,
+    schedule: Schedule,
+    shutdown: Shutdown,
+}
+
+impl<'a, T: Transport> Tracker<'a, T> {
+    /// Tracks the ISS through `client`, polling every `interval`.
+    /// The maximal gap defaults to twice the interval. Fails with
+    /// `OpenNotificationError::InvalidArgument` for a zero interval.
+    pub fn new(
+        client: &'a OpenNotifyClient<T>,
+        interval: Duration,
+    ) -> Result<Tracker<'a, T>, OpenNotificationError> {
+        Ok(Tracker {
+            client,
+            schedule: Schedule::new(interval)?,
+            shutdown: Shutdown::new(),
+        })
+    }
+
+    /// Time between two samples above which a gap is reported.
+    pub fn max_gap(mut self, max_gap: Duration) -> Tracker<'a, T> {
+        self.schedule.max_gap = max_gap;
+        self
+    }
+
+    /// Handle stopping this tracker.
+    pub fn shutdown_handle(&self) -> Shutdown {
+        self.shutdown.clone()
+    }
+}
+
+impl<'a, T: Transport> Iterator for Tracker<'a, T> {
+    type Item = Result<Sample, OpenNotificationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.shutdown.wait_until(self.schedule.due(Instant::now())) {
+                return None;
+            }
+            self.schedule.polled(Instant::now());
+
+            match self.client.iss_now() {
+                Ok(iss_now) => {
+                    if let Some(sample) = self.schedule.accept(iss_now) {
+                        return Some(Ok(sample));
+                    }
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Polls the ISS position at a fixed interval without blocking.
+///
+/// Independent of an async runtime, the waits for the next poll are
+/// timed by a thread started on the first wait, which ends when the
+/// tracker is dropped.
+#[cfg(feature = "async")]
+pub struct AsyncTracker<'a, T: 'a> {
+    client: &'a AsyncOpenNotifyClient<T>,
+    schedule: Schedule,
+    shutdown: Shutdown,
+    timer: Option<Timer>,
+    fetch: Option<Fetch<'a, IssNow>>,
+}
+
+#[cfg(feature = "async")]
+impl<'a, T: AsyncTransport> AsyncTracker<'a, T> {
+    /// Tracks the ISS through `client`, polling every `interval`.
+    /// The maximal gap defaults to twice the interval. Fails with
+    /// `OpenNotificationError::InvalidArgument` for a zero interval.
+    pub fn new(
+        client: &'a AsyncOpenNotifyClient<T>,
+        interval: Duration,
+    ) -> Result<AsyncTracker<'a, T>, OpenNotificationError> {
+        Ok(AsyncTracker {
+            client,
+            schedule: Schedule::new(interval)?,
+            shutdown: Shutdown::new(),
+            timer: None,
+            fetch: None,
+        })
+    }
+
+    /// Time between two samples above which a gap is reported.
+    pub fn max_gap(mut self, max_gap: Duration) -> AsyncTracker<'a, T> {
+        self.schedule.max_gap = max_gap;
+        self
+    }
+
+    /// Handle stopping this tracker.
+    pub fn shutdown_handle(&self) -> Shutdown {
+        self.shutdown.clone()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T: AsyncTransport> Stream for AsyncTracker<'a, T> {
+    type Item = Result<Sample, OpenNotificationError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.shutdown.is_shutdown() {
+                this.fetch = None;
+                return Poll::Ready(None);
+            }
+
+            if let Some(ref mut fetch) = this.fetch {
+                let result = match Pin::new(fetch).poll(cx) {
+                    Poll::Ready(result) => result,
+                    Poll::Pending => return Poll::Pending,
+                };
+                this.fetch = None;
+                match result {
+                    Ok(iss_now) => match this.schedule.accept(iss_now) {
+                        Some(sample) => return Poll::Ready(Some(Ok(sample))),
+                        None => continue,
+                    },
+                    Err(e) => return Poll::Ready(Some(Err(e))),
+                }
+            }
+
+            let now = Instant::now();
+            let due = this.schedule.due(now);
+            if now < due {
+                this.shutdown.wake_on_shutdown(cx);
+                if this.shutdown.is_shutdown() {
+                    continue;
+                }
+                this.timer.get_or_insert_with(Timer::new).wake_at(due, cx);
+                return Poll::Pending;
+            }
+
+            this.schedule.polled(now);
+            this.fetch = Some(this.client.iss_now());
+        }
+    }
+}
+
+/// Thread waking a task at a deadline.
+#[cfg(feature = "async")]
+struct Timer {
+    requests: Sender<(Instant, Waker)>,
+}
+
+#[cfg(feature = "async")]
+impl Timer {
+    /// Starts the thread, which ends once the timer is dropped.
+    fn new() -> Timer {
+        let (requests, received) = mpsc::channel::<(Instant, Waker)>();
+        thread::spawn(move || {
+            let mut pending: Option<(Instant, Waker)> = None;
+            loop {
+                let request = match pending {
+                    Some((deadline, _)) => {
+                        let now = Instant::now();
+                        if now >= deadline {
+                            pending.take().unwrap().1.wake();
+                            continue;
+                        }
+                        received.recv_timeout(deadline - now)
+                    }
+                    None => received.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match request {
+                    Ok(request) => pending = Some(request),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        });
+        Timer { requests }
+    }
+
+    /// Wakes the task of `cx` at `deadline`, replacing the previous
+    /// request.
+    fn wake_at(&self, deadline: Instant, cx: &Context) {
+        // The thread only ends when the timer is dropped.
+        let _ = self.requests.send((deadline, cx.waker().clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::thread;
+    use transport::Response;
+
+    /// Serves a fixed sequence of `iss-now.json` timestamps.
+    struct Sequence {
+        responses: Mutex<VecDeque<Response>>,
+    }
+
+    impl Sequence {
+        fn new(timestamps: &[i64]) -> Sequence {
+            let responses = timestamps
+                .iter()
+                .map(|timestamp| {
+                    let body = format!(
+                        r#"{{"message": "success", "timestamp": {},
+                        "iss_position": {{"latitude": 1.0, "longitude": 2.0}}}}"#,
+                        timestamp
+                    );
+                    Response::new(200, &body)
+                })
+                .collect();
+            Sequence {
+                responses: Mutex::new(responses),
+            }
+        }
+    }
+
+    impl Transport for Sequence {
+        fn get(&self, _url: &str) -> Result<Response, OpenNotificationError> {
+            Ok(self
+                .responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| Response::new(503, "")))
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncTransport for Sequence {
+        fn get(&self, url: &str) -> ::transport::TransportFuture<'_> {
+            Box::pin(::std::future::ready(Transport::get(self, url)))
+        }
+    }
+
+    #[test]
+    fn tracker_drops_duplicates_and_reports_gaps() {
+        let client = OpenNotifyClient::with_transport(Sequence::new(&[100, 100, 99, 101, 110]));
+        let mut tracker = Tracker::new(&client, Duration::from_millis(1))
+            .unwrap()
+            .max_gap(Duration::from_secs(5));
+
+        let first = tracker.next().unwrap().unwrap();
+        assert_eq!(first.iss_now().timestamp(), 100);
+        assert_eq!(first.gap(), None);
+
+        let second = tracker.next().unwrap().unwrap();
+        assert_eq!(second.iss_now().timestamp(), 101);
+        assert_eq!(second.gap(), None);
+
+        let third = tracker.next().unwrap().unwrap();
+        assert_eq!(third.iss_now().timestamp(), 110);
+        assert_eq!(third.gap(), Some(Duration::from_secs(9)));
+
+        match tracker.next() {
+            Some(Err(OpenNotificationError::Http { status: 503, .. })) => {}
+            _ => panic!("expected a HTTP error"),
+        }
+
+        tracker.shutdown_handle().shutdown();
+        assert!(tracker.next().is_none());
+    }
+
+    #[test]
+    fn tracker_shutdown_while_waiting() {
+        let client = OpenNotifyClient::with_transport(Sequence::new(&[100, 101]));
+        let mut tracker = Tracker::new(&client, Duration::from_secs(60)).unwrap();
+        let shutdown = tracker.shutdown_handle();
+
+        assert!(tracker.next().unwrap().is_ok());
+
+        let started = Instant::now();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            shutdown.shutdown();
+        });
+        assert!(tracker.next().is_none());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn tracker_rejects_zero_interval() {
+        let client = OpenNotifyClient::with_transport(Sequence::new(&[]));
+        match Tracker::new(&client, Duration::from_secs(0)) {
+            Err(OpenNotificationError::InvalidArgument(_)) => {}
+            _ => panic!("expected an invalid argument"),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_tracker_shutdown_while_waiting() {
+        use client::OpenNotifyClientBuilder;
+        use futures::executor::block_on;
+        use futures::stream::StreamExt;
+
+        let client =
+            OpenNotifyClientBuilder::new().build_async_with_transport(Sequence::new(&[100, 101]));
+        let mut tracker = AsyncTracker::new(&client, Duration::from_secs(60)).unwrap();
+        let shutdown = tracker.shutdown_handle();
+
+        assert!(block_on(tracker.next()).unwrap().is_ok());
+
+        let started = Instant::now();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            shutdown.shutdown();
+        });
+        assert!(block_on(tracker.next()).is_none());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_tracker_stream() {
+        use client::OpenNotifyClientBuilder;
+        use futures::executor::block_on;
+        use futures::stream::StreamExt;
+
+        let client =
+            OpenNotifyClientBuilder::new().build_async_with_transport(Sequence::new(&[5, 5, 6]));
+        let mut tracker = AsyncTracker::new(&client, Duration::from_millis(5)).unwrap();
+        let shutdown = tracker.shutdown_handle();
+
+        let first = block_on(tracker.next()).unwrap().unwrap();
+        assert_eq!(first.iss_now().timestamp(), 5);
+        let second = block_on(tracker.next()).unwrap().unwrap();
+        assert_eq!(second.iss_now().timestamp(), 6);
+
+        shutdown.shutdown();
+        assert!(block_on(tracker.next()).is_none());
+    }
+}
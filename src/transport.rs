@@ -0,0 +1,302 @@
+//! HTTP transports used by `OpenNotifyClient`.
+//!
+//! The client only needs to issue GET requests, so any HTTP stack
+//! can be plugged in by implementing `Transport`. A `reqwest` based
+//! transport is available with the `reqwest` feature (enabled by
+//! default) and `MemoryTransport` serves canned responses for tests.
+//!
+//! With the `async` feature the same applies to `AsyncTransport`,
+//! which backs `AsyncOpenNotifyClient`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "reqwest")]
+use std::time::Duration;
+
+#[cfg(all(feature = "async", feature = "reqwest"))]
+use futures::future::{self, TryFutureExt};
+#[cfg(feature = "reqwest")]
+use reqwest;
+
+use error::OpenNotificationError;
+
+/// Status code and body of a HTTP response.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    pub fn new(status: u16, body: &str) -> Response {
+        Response {
+            status,
+            body: String::from(body),
+        }
+    }
+
+    /// HTTP status code
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// Response body
+    pub fn body(&self) -> &str {
+        self.body.as_str()
+    }
+}
+
+/// Minimal HTTP interface needed to talk to open-notify.
+pub trait Transport {
+    /// Sends a GET request to `url` and returns the response.
+    ///
+    /// Failing to reach the server is reported as
+    /// `OpenNotificationError::Network`. A response with an error
+    /// status code is still a valid `Response`.
+    fn get(&self, url: &str) -> Result<Response, OpenNotificationError>;
+}
+
+impl<T: Transport + ?Sized> Transport for &T {
+    fn get(&self, url: &str) -> Result<Response, OpenNotificationError> {
+        (**self).get(url)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn get(&self, url: &str) -> Result<Response, OpenNotificationError> {
+        (**self).get(url)
+    }
+}
+
+/// Future returned by `AsyncTransport::get`.
+#[cfg(feature = "async")]
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Response, OpenNotificationError>> + Send + 'a>>;
+
+/// Non-blocking counterpart of `Transport`.
+#[cfg(feature = "async")]
+pub trait AsyncTransport {
+    /// Sends a GET request to `url` and resolves to the response.
+    /// Nothing is sent before the future is first polled.
+    ///
+    /// Error handling is the same as for `Transport::get`.
+    fn get(&self, url: &str) -> TransportFuture<'_>;
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncTransport + ?Sized> AsyncTransport for &T {
+    fn get(&self, url: &str) -> TransportFuture<'_> {
+        (**self).get(url)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncTransport + ?Sized> AsyncTransport for Box<T> {
+    fn get(&self, url: &str) -> TransportFuture<'_> {
+        (**self).get(url)
+    }
+}
+
+/// Transport backed by a blocking `reqwest::blocking::Client`.
+///
+/// Connections are pooled and shared between clones. Like the
+/// underlying client it must not be created or dropped from within
+/// an async runtime, use `AsyncReqwestTransport` there instead.
+#[cfg(feature = "reqwest")]
+#[derive(Clone)]
+pub struct ReqwestTransport {
+    http: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestTransport {
+    /// Creates a transport with default settings.
+    pub fn new() -> Result<ReqwestTransport, OpenNotificationError> {
+        ReqwestTransport::with_options(None, None)
+    }
+
+    /// Creates a transport with an optional per request timeout
+    /// and `User-Agent` header.
+    pub fn with_options(
+        timeout: Option<Duration>,
+        user_agent: Option<&str>,
+    ) -> Result<ReqwestTransport, OpenNotificationError> {
+        let mut http = reqwest::blocking::Client::builder();
+
+        if let Some(timeout) = timeout {
+            http = http.timeout(timeout);
+        }
+
+        if let Some(user_agent) = user_agent {
+            http = http.user_agent(user_agent);
+        }
+
+        Ok(ReqwestTransport {
+            http: http.build()?,
+        })
+    }
+
+    /// Wraps an already configured `reqwest::blocking::Client`.
+    pub fn from_client(http: reqwest::blocking::Client) -> ReqwestTransport {
+        ReqwestTransport { http }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl Transport for ReqwestTransport {
+    fn get(&self, url: &str) -> Result<Response, OpenNotificationError> {
+        let response = self.http.get(url).send()?;
+        Ok(Response {
+            status: response.status().as_u16(),
+            body: response.text()?,
+        })
+    }
+}
+
+/// Transport backed by an async `reqwest::Client`.
+///
+/// Requires a tokio runtime. Connections are pooled and shared
+/// between clones.
+#[cfg(all(feature = "async", feature = "reqwest"))]
+#[derive(Clone)]
+pub struct AsyncReqwestTransport {
+    http: reqwest::Client,
+}
+
+#[cfg(all(feature = "async", feature = "reqwest"))]
+impl AsyncReqwestTransport {
+    /// Creates a transport with default settings.
+    pub fn new() -> Result<AsyncReqwestTransport, OpenNotificationError> {
+        AsyncReqwestTransport::with_options(None, None)
+    }
+
+    /// Creates a transport with an optional per request timeout
+    /// and `User-Agent` header.
+    pub fn with_options(
+        timeout: Option<Duration>,
+        user_agent: Option<&str>,
+    ) -> Result<AsyncReqwestTransport, OpenNotificationError> {
+        let mut http = reqwest::Client::builder();
+
+        if let Some(timeout) = timeout {
+            http = http.timeout(timeout);
+        }
+
+        if let Some(user_agent) = user_agent {
+            http = http.user_agent(user_agent);
+        }
+
+        Ok(AsyncReqwestTransport {
+            http: http.build()?,
+        })
+    }
+
+    /// Wraps an already configured `reqwest::Client`.
+    pub fn from_client(http: reqwest::Client) -> AsyncReqwestTransport {
+        AsyncReqwestTransport { http }
+    }
+}
+
+#[cfg(all(feature = "async", feature = "reqwest"))]
+impl AsyncTransport for AsyncReqwestTransport {
+    fn get(&self, url: &str) -> TransportFuture<'_> {
+        let response = self.http.get(url).send().and_then(|response| {
+            let status = response.status().as_u16();
+            response
+                .text()
+                .and_then(move |body| future::ok(Response { status, body }))
+        });
+        Box::pin(response.map_err(OpenNotificationError::from))
+    }
+}
+
+/// Transport answering requests from a fixed set of responses.
+///
+/// Requests for unknown urls are answered with status 404.
+///
+/// # Example
+/// ```
+/// use open_notify_api::OpenNotifyClient;
+/// use open_notify_api::transport::MemoryTransport;
+///
+/// let mut transport = MemoryTransport::new();
+/// transport.insert(
+///     "http://api.open-notify.org/iss-now.json",
+///     200,
+///     r#"{"message": "success", "timestamp": 1521971230,
+///         "iss_position": {"latitude": -34.6445, "longitude": 73.5964}}"#,
+/// );
+///
+/// let client = OpenNotifyClient::with_transport(transport);
+/// assert_eq!(client.iss_now().unwrap().timestamp(), 1521971230);
+/// ```
+#[derive(Default)]
+pub struct MemoryTransport {
+    responses: HashMap<String, Response>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl MemoryTransport {
+    pub fn new() -> MemoryTransport {
+        MemoryTransport::default()
+    }
+
+    /// Registers the response returned for requests to `url`.
+    pub fn insert(&mut self, url: &str, status: u16, body: &str) {
+        self.responses
+            .insert(String::from(url), Response::new(status, body));
+    }
+
+    /// Urls requested so far, oldest first.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn get(&self, url: &str) -> Result<Response, OpenNotificationError> {
+        self.requests.lock().unwrap().push(String::from(url));
+
+        Ok(match self.responses.get(url) {
+            Some(response) => response.clone(),
+            None => Response::new(404, "not found"),
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransport for MemoryTransport {
+    fn get(&self, url: &str) -> TransportFuture<'_> {
+        let url = String::from(url);
+        Box::pin(::futures::future::lazy(move |_| Transport::get(self, &url)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_transport_known_url() {
+        let mut transport = MemoryTransport::new();
+        transport.insert("http://localhost/astros.json", 200, "{}");
+
+        let response = Transport::get(&transport, "http://localhost/astros.json").unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.body(), "{}");
+    }
+
+    #[test]
+    fn memory_transport_unknown_url() {
+        let transport = MemoryTransport::new();
+
+        let response = Transport::get(&transport, "http://localhost/astros.json").unwrap();
+        assert_eq!(response.status(), 404);
+        assert_eq!(transport.requests(), vec!["http://localhost/astros.json"]);
+    }
+}
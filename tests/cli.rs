@@ -0,0 +1,74 @@
+extern crate open_notify_api;
+
+use std::process::{Command, Output};
+
+use open_notify_api::mock::{MockResponse, MockServer};
+
+fn open_notify(server: &MockServer, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_open-notify"))
+        .arg("--base-url")
+        .arg(server.url())
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn astros_table() {
+    let server = MockServer::start().unwrap();
+
+    let output = open_notify(&server, &["astros"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("NAME"));
+    assert!(stdout.ends_with("6 people in space\n"));
+}
+
+#[test]
+fn now_json() {
+    let server = MockServer::start().unwrap();
+
+    let output = open_notify(&server, &["--format", "json", "now"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "{\"latitude\":-34.6445,\"longitude\":73.5964,\"timestamp\":1521971230}\n"
+    );
+}
+
+#[test]
+fn passes_csv() {
+    let server = MockServer::start().unwrap();
+
+    let output = open_notify(
+        &server,
+        &[
+            "--format", "csv", "passes", "--lat", "52.5", "--lon", "13.4", "--alt", "10",
+        ],
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().next(), Some("risetime,settime,duration"));
+    assert_eq!(stdout.lines().count(), 6);
+}
+
+#[test]
+fn readable_errors() {
+    let server = MockServer::start_empty().unwrap();
+    server.set("/iss-now.json", MockResponse::failure("out of orbit"));
+
+    let output = open_notify(&server, &["now"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("out of orbit"));
+
+    let output = open_notify(&server, &["passes", "--lat", "95", "--lon", "0"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("error: invalid argument"));
+
+    let output = open_notify(&server, &["passes"]);
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,184 @@
+extern crate open_notify_api;
+
+use std::thread;
+use std::time::Duration;
+
+use open_notify_api::error::OpenNotificationError;
+use open_notify_api::mock::{MockResponse, MockServer};
+use open_notify_api::retry::{CircuitBreaker, RetryPolicy};
+use open_notify_api::transport::ReqwestTransport;
+use open_notify_api::OpenNotifyClient;
+
+fn client(server: &MockServer) -> OpenNotifyClient<ReqwestTransport> {
+    OpenNotifyClient::builder()
+        .base_url(&server.url())
+        .timeout(Duration::from_millis(500))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn astros_fixture() {
+    let server = MockServer::start().unwrap();
+
+    let astros = client(&server).astros().unwrap();
+    assert_eq!(astros.people().len(), 6);
+    assert_eq!(server.requests(), vec!["/astros.json"]);
+}
+
+#[test]
+fn iss_now_fixture() {
+    let server = MockServer::start().unwrap();
+
+    let iss_now = client(&server).iss_now().unwrap();
+    assert_eq!(iss_now.timestamp(), 1521971230);
+}
+
+#[test]
+fn iss_pass_times_fixture() {
+    let server = MockServer::start().unwrap();
+
+    let pass_times = client(&server).iss_pass_times(52.5, 13.4, 10.0, 5).unwrap();
+    assert_eq!(pass_times.passes().len(), 5);
+    assert_eq!(
+        server.requests(),
+        vec!["/iss-pass.json?lat=52.5&lon=13.4&alt=10&n=5"]
+    );
+}
+
+#[test]
+fn upstream_failure() {
+    let server = MockServer::start().unwrap();
+    server.set("/iss-now.json", MockResponse::failure("maintenance"));
+
+    match client(&server).iss_now() {
+        Err(OpenNotificationError::Upstream(reason)) => assert_eq!(reason, "maintenance"),
+        _ => panic!("expected an upstream failure"),
+    }
+}
+
+#[test]
+fn malformed_body() {
+    let server = MockServer::start().unwrap();
+    server.set("/astros.json", MockResponse::malformed());
+
+    match client(&server).astros() {
+        Err(OpenNotificationError::Parsing(_)) => {}
+        _ => panic!("expected a parsing error"),
+    }
+}
+
+#[test]
+fn slow_response() {
+    let server = MockServer::start().unwrap();
+    server.enqueue(
+        "/iss-now.json",
+        MockResponse::json("{}").delayed(Duration::from_secs(2)),
+    );
+
+    match client(&server).iss_now() {
+        Err(OpenNotificationError::Timeout) => {}
+        _ => panic!("expected a timeout"),
+    }
+
+    assert!(client(&server).iss_now().is_ok());
+}
+
+fn retry_policy() -> RetryPolicy {
+    RetryPolicy::new(3).initial_backoff(Duration::from_millis(10))
+}
+
+#[test]
+fn retry_transient_errors() {
+    let server = MockServer::start().unwrap();
+    server.enqueue("/astros.json", MockResponse::new(503, "unavailable"));
+    server.enqueue(
+        "/astros.json",
+        MockResponse::json("{}").delayed(Duration::from_secs(2)),
+    );
+
+    let client = OpenNotifyClient::builder()
+        .base_url(&server.url())
+        .timeout(Duration::from_millis(500))
+        .retry(retry_policy())
+        .build()
+        .unwrap();
+
+    assert_eq!(client.astros().unwrap().people().len(), 6);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn retry_gives_up() {
+    let server = MockServer::start().unwrap();
+    server.set("/iss-now.json", MockResponse::new(500, "oops"));
+
+    let client = OpenNotifyClient::builder()
+        .base_url(&server.url())
+        .retry(retry_policy())
+        .build()
+        .unwrap();
+
+    match client.iss_now() {
+        Err(OpenNotificationError::Http { status, body }) => {
+            assert_eq!(status, 500);
+            assert_eq!(body, "oops");
+        }
+        _ => panic!("expected a HTTP error"),
+    }
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn no_retry_on_upstream_failure() {
+    let server = MockServer::start().unwrap();
+    server.set("/iss-now.json", MockResponse::failure("maintenance"));
+
+    let client = OpenNotifyClient::builder()
+        .base_url(&server.url())
+        .retry(retry_policy())
+        .build()
+        .unwrap();
+
+    assert!(client.iss_now().is_err());
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn circuit_breaker_fails_fast() {
+    let server = MockServer::start().unwrap();
+    server.enqueue("/iss-now.json", MockResponse::new(503, "unavailable"));
+    server.enqueue("/iss-now.json", MockResponse::new(503, "unavailable"));
+
+    let client = OpenNotifyClient::builder()
+        .base_url(&server.url())
+        .circuit_breaker(CircuitBreaker::new(2, Duration::from_millis(200)))
+        .build()
+        .unwrap();
+
+    assert!(client.iss_now().is_err());
+    assert!(client.iss_now().is_err());
+    match client.iss_now() {
+        Err(OpenNotificationError::CircuitOpen) => {}
+        _ => panic!("expected an open circuit"),
+    }
+    assert_eq!(server.requests().len(), 2);
+
+    thread::sleep(Duration::from_millis(250));
+    assert!(client.iss_now().is_ok());
+    assert!(!client.circuit_breaker().unwrap().is_open());
+}
+
+#[test]
+fn error_status() {
+    let server = MockServer::start().unwrap();
+    server.set("/astros.json", MockResponse::new(404, "<h1>Not Found</h1>"));
+
+    match client(&server).astros() {
+        Err(OpenNotificationError::Http { status, body }) => {
+            assert_eq!(status, 404);
+            assert_eq!(body, "<h1>Not Found</h1>");
+        }
+        _ => panic!("expected a HTTP error"),
+    }
+}